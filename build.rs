@@ -0,0 +1,75 @@
+// Compiles src/shaders to SPIR-V in OUT_DIR, and writes the layout of the uniform block
+// as assertions on UniformBufferObject, which src/cameras/camera4.rs includes.
+
+#[allow(dead_code)]
+#[path = "src/pipelines/glsl.rs"]
+mod glsl;
+
+use naga::{AddressSpace, Module, ShaderStage, TypeInner};
+
+use std::fmt::Write;
+use std::path::Path;
+
+const SHADERS: [(&str, ShaderStage, &str); 2] = [
+    ("src/shaders/shader.vert", ShaderStage::Vertex, "vert.spv"),
+    ("src/shaders/shader.frag", ShaderStage::Fragment, "frag.spv"),
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/shaders");
+    println!("cargo:rerun-if-changed=src/pipelines/glsl.rs");
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let out_dir = Path::new(&out_dir);
+
+    for (path, stage, spv) in SHADERS {
+        let source = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+
+        let module = glsl::parse(&source, stage).unwrap_or_else(|e| panic!("{}:\n{}", path, e));
+        let code = glsl::write_spv(&module).unwrap_or_else(|e| panic!("{}: {}", path, e));
+        let bytes: Vec<u8> = code.iter().flat_map(|word| word.to_le_bytes()).collect();
+        std::fs::write(out_dir.join(spv), bytes).unwrap();
+
+        if stage == ShaderStage::Vertex {
+            std::fs::write(out_dir.join("uniform_layout.rs"), uniform_layout(&module)).unwrap();
+        }
+    }
+}
+
+fn uniform_layout(module: &Module) -> String {
+    let (_, uniform) = module
+        .global_variables
+        .iter()
+        .find(|(_, global)| global.space == AddressSpace::Uniform)
+        .expect("shader.vert has no uniform block");
+
+    let (members, span) = match &module.types[uniform.ty].inner {
+        TypeInner::Struct { members, span } => (members, *span),
+        _ => panic!("the uniform of shader.vert is not a block"),
+    };
+
+    let mut text = String::new();
+    let mut end = 0;
+
+    for member in members {
+        let name = member.name.as_deref().expect("a member of the uniform block has no name");
+        writeln!(
+            text,
+            "const _: () = assert!(std::mem::offset_of!(UniformBufferObject, {0}) == {1}, \
+             \"UniformBufferObject::{0} is not at offset {1} like in shader.vert\");",
+            name, member.offset
+        ).unwrap();
+
+        end = member.offset + module.types[member.ty].inner.size(&module.constants);
+    }
+
+    writeln!(
+        text,
+        "const _: () = assert!(std::mem::size_of::<UniformBufferObject>() >= {0} \
+         && std::mem::size_of::<UniformBufferObject>() <= {1}, \
+         \"UniformBufferObject does not have the size of the uniform block in shader.vert, {0} to {1} bytes\");",
+        end, span
+    ).unwrap();
+
+    text
+}
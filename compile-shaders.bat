@@ -1,2 +0,0 @@
-glslc.exe src/shaders/shader.vert -o data/shaders/vert.spv
-glslc.exe src/shaders/shader.frag -o data/shaders/frag.spv
\ No newline at end of file
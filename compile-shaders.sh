@@ -1,4 +0,0 @@
-#!/bin/bash
-
-/usr/bin/glslc src/shaders/shader.vert -o data/shaders/vert.spv
-/usr/bin/glslc src/shaders/shader.frag -o data/shaders/frag.spv
\ No newline at end of file
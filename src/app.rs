@@ -1,18 +1,23 @@
 
 use crate::config::*;
-use crate::pipelines::Pipeline;
+use crate::error::AppError;
+use crate::cli::Cli;
+use crate::pipelines::{Pipeline, ShaderCode, ShaderWatcher};
 use crate::buffers::Buffer;
-use crate::entities::{Vertex, Entity};
+use crate::entities::{Vertex, Entity, Unfolding};
+use crate::ui::{animation_ui, clock_ui, Inspector, Labels, ScenePicker};
+use crate::utils::Clock;
+use crate::capture::{record_readback, save_readback, Offscreen, Recorder, Screenshot};
+use crate::editor::{Gizmo, Selection};
 
 use crate::cameras::Camera as CameraTrait;
-use crate::cameras::{UniformBufferObject, Camera4 as Camera};
+use crate::cameras::{UniformBufferObject, Camera4 as Camera, Stereo, Viewports};
 
-use nalgebra as na;
 use winit::{
     event,
-    event::{Event, WindowEvent},
+    event::{DeviceEvent, ElementState, Event, KeyboardInput, MouseButton, WindowEvent},
     event_loop::EventLoop,
-    window::{Window, WindowBuilder},
+    window::{Fullscreen, Window, WindowBuilder},
 };
 
 use gpu_allocator::vulkan::*;
@@ -26,20 +31,34 @@ use ash::extensions::{
 
 use ash::vk;
 
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 // use std::mem::ManuallyDrop;
 // use std::ptr;
 // use once_cell::unsync::OnceCell;
 // use std::rc::Rc;
 
+// The most renderings of the scene in one frame, each with its own uniform buffer:
+// two eyes in each of four viewports.
+const MAX_VIEWS: usize = 8;
+
 #[derive(Default)]
 pub struct App {
     window: Option<Window>,
     entry: Option<ash::Entry>,
     instance: Option<ash::Instance>,
     max_frames_in_flight: Option<usize>,
-
+    // the requested and the actual mode of the current swapchain
+    present_mode: Option<(PresentMode, vk::PresentModeKHR)>,
+    // the mode of the current pipeline and index buffer
+    render_mode: Option<RenderMode>,
+    settings: Settings,
+
+    // the validation layer and the debug messenger are only used when asked for
+    validation: bool,
+    // chooses the GPU instead of the best scored one
+    device_override: Option<String>,
     debug_utils_loader: Option<DebugUtils>,
     debug_utils_messenger: Option<vk::DebugUtilsMessengerEXT>,
 
@@ -58,6 +77,10 @@ pub struct App {
     swapchain_images: Option<Vec<vk::Image>>,
     swapchain_image_views: Option<Vec<vk::ImageView>>,
     swapchain_image_count: Option<usize>,
+    // set when the surface changed, the swapchain is recreated before the next frame
+    swapchain_outdated: bool,
+    // false while `new` hasn't finished
+    initialized: bool,
 
     command_pool: Option<vk::CommandPool>,
     setup_command_buffer: Option<vk::CommandBuffer>,
@@ -82,42 +105,79 @@ pub struct App {
     descriptor_sets: Option<Vec<vk::DescriptorSet>>,
     
     pipeline: Option<Pipeline>,
+    shaders: Option<ShaderCode>,
+    // set in the development mode that reloads changed shaders
+    shader_watcher: Option<ShaderWatcher>,
 
     entities: Option<Vec<Entity>>,
-    camera: Option<Camera>,
+    viewports: Option<Viewports>,
+
+    unfolding: Option<Unfolding>,
+    unfold_amount: f32,
+    unfolded_amount: f32,
+
+    scenes: ScenePicker,
+    labels: Labels,
+    inspector: Inspector,
+
+    cursor_grabbed: bool,
+    cursor: Option<egui::Pos2>,
+    selection: Option<Selection>,
+    gizmo: Gizmo,
+
+    clock: Option<Clock>,
+    recorder: Recorder,
+    screenshot: Screenshot,
+    stereo: Stereo,
+    capture_supported: bool,
 
     uniform_buffers: Option<Vec<Buffer>>,
     vertex_buffers: Option<Vec<Buffer>>,
     index_buffers: Option<Vec<Buffer>>,
+    index_count: u32,
 
     egui_integration: Option<Integration<Arc<Mutex<Allocator>>>>,
 }
 
 impl App {
-    pub fn new(event_loop: &EventLoop<()>) -> Self {
+    pub fn new(event_loop: &EventLoop<()>, cli: &Cli, settings: Settings) -> Result<Self, AppError> {
         let mut app = Self::default();
 
-        app.max_frames_in_flight = Some(2);
+        app.validation = cli.validation;
+        app.device_override = cli.device.clone();
+        app.shaders = Some(ShaderCode::embedded()?);
+        if cli.watch_shaders {
+            app.shader_watcher = Some(ShaderWatcher::new());
+        }
+
+        app.max_frames_in_flight = Some(settings.frames_in_flight);
+        app.render_mode = Some(settings.render_mode);
+        app.settings = settings;
         app.current_frame = Some(0);
+        app.clock = Some(Clock::new());
+
+        app.create_window(event_loop)?;
 
+        let size = app.window.as_ref().unwrap().inner_size();
         app.surface_resolution = Some(
             vk::Extent2D {
-                width: WINDOW_WIDTH,
-                height: WINDOW_WIDTH,
+                width: size.width,
+                height: size.height,
             }
         );
 
-        app.create_window(event_loop);
-        app.init_entry();
-        app.create_instance();
-        app.init_debug_utils_loader();
-        app.create_surface();
-        app.pick_physical_device();
-        app.create_logical_device();
+        app.init_entry()?;
+        app.create_instance()?;
+        if app.validation {
+            app.init_debug_utils_loader();
+        }
+        app.create_surface()?;
+        app.pick_physical_device()?;
+        app.create_logical_device()?;
 
         app.create_allocator();
 
-        app.create_swapchain();
+        app.create_swapchain()?;
         app.create_image_views();
 
         app.create_command_pool();
@@ -127,11 +187,12 @@ impl App {
         app.create_depth_resource();
 
         app.create_descriptor_set_layouts();
-        app.create_pipeline();
+        app.create_pipeline()?;
 
         app.create_egui_integration(event_loop);
 
-        app.create_entities();
+        app.create_entities()?;
+        app.create_unfolding();
         app.create_camera();
         app.create_vertex_buffers();
         app.create_index_buffer();
@@ -145,38 +206,131 @@ impl App {
         app.create_draw_command_buffers();
         app.create_sync_objects();
 
-        app
+        app.initialized = true;
+        Ok(app)
     }
 
-    fn create_window(&mut self, event_loop: &EventLoop<()>) {
+    // Only what `render_still` needs, without a window or a surface,
+    // so that stills render on machines without a display.
+    pub fn headless(cli: &Cli, settings: Settings) -> Result<Self, AppError> {
+        let mut app = Self::default();
+
+        app.validation = cli.validation;
+        app.device_override = cli.device.clone();
+        app.shaders = Some(ShaderCode::embedded()?);
+
+        app.render_mode = Some(settings.render_mode);
+        app.surface_resolution = Some(vk::Extent2D {
+            width: settings.window.width,
+            height: settings.window.height,
+        });
+        app.settings = settings;
+        app.clock = Some(Clock::new());
+
+        // one set of uniform buffers, in the format most windows get
+        app.swapchain_image_count = Some(1);
+        app.surface_format = Some(vk::SurfaceFormatKHR {
+            format: vk::Format::B8G8R8A8_SRGB,
+            color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR,
+        });
+
+        app.init_entry()?;
+        app.create_instance()?;
+        if app.validation {
+            app.init_debug_utils_loader();
+        }
+        app.pick_physical_device()?;
+        app.create_logical_device()?;
+
+        app.create_allocator();
+
+        app.create_command_pool();
+        app.create_setup_command_buffer();
+        app.create_graphic_queue();
+
+        app.create_descriptor_set_layouts();
+        app.create_pipeline()?;
 
+        app.create_entities()?;
+        app.create_unfolding();
+        app.create_camera();
+        app.create_vertex_buffers();
+        app.create_index_buffer();
+        app.create_uniform_buffers();
+
+        app.create_descriptor_pool();
+        app.create_descriptor_sets();
+
+        app.initialized = true;
+        Ok(app)
+    }
+
+    fn create_window(&mut self, event_loop: &EventLoop<()>) -> Result<(), AppError> {
+
+        let settings = &self.settings.window;
         let window = WindowBuilder::new()
-            .with_title(WINDOW_TITLE)
-            .with_inner_size(winit::dpi::LogicalSize::new(WINDOW_WIDTH, WINDOW_HEIGHT))
-            .build(&event_loop)
-            .expect("Failed to create window.");
-        
-            // Grab and hide the cursor
-        window.set_cursor_visible(false);
-        window.set_cursor_grab(winit::window::CursorGrabMode::Confined)
-            .or_else(|_e| window.set_cursor_grab(winit::window::CursorGrabMode::Locked))
-            .expect("Failed to grab the cursor.");
+            .with_title(APP_NAME)
+            .with_inner_size(winit::dpi::LogicalSize::new(settings.width, settings.height))
+            .with_fullscreen(settings.fullscreen.then(|| Fullscreen::Borderless(None)))
+            .build(&event_loop)?;
 
         self.window = Some(window);
+        self.set_cursor_grabbed(true);
+
+        Ok(())
+    }
+
+    // A grabbed and hidden cursor flies the camera, a free one picks and edits.
+    // Some compositors refuse the grab, then the cursor stays free.
+    fn set_cursor_grabbed(&mut self, grabbed: bool) {
+        let window = self.window.as_ref().unwrap();
+
+        if grabbed {
+            let grab = window.set_cursor_grab(winit::window::CursorGrabMode::Confined)
+                .or_else(|_e| window.set_cursor_grab(winit::window::CursorGrabMode::Locked));
+            if let Err(e) = grab {
+                log::warn!("Could not grab the cursor, it stays free: {}", e);
+                return;
+            }
+        } else if let Err(e) = window.set_cursor_grab(winit::window::CursorGrabMode::None) {
+            log::warn!("Could not release the cursor: {}", e);
+        }
+        window.set_cursor_visible(!grabbed);
+
+        self.cursor_grabbed = grabbed;
+    }
+
+    fn pick(&mut self) {
+        let cursor = match self.cursor {
+            Some(cursor) => cursor,
+            None => return,
+        };
+
+        let egui_integration = self.egui_integration.as_ref().unwrap();
+        let context = egui_integration.context();
+        if context.wants_pointer_input() {
+            return;
+        }
+
+        let screen = context.input().screen_rect();
+        let viewports = self.viewports.as_mut().unwrap();
+        let entities = self.entities.as_ref().unwrap();
+
+        viewports.activate_at(cursor, screen);
+        self.selection = Selection::pick(viewports.active(), entities, cursor, viewports.active_rect(screen));
     }
     
-    fn init_entry(&mut self) {
-        let entry = ash::Entry::linked();
+    fn init_entry(&mut self) -> Result<(), AppError> {
+        let entry = unsafe { ash::Entry::load()? };
 
         self.entry = Some(entry);
+
+        Ok(())
     }
 
-    fn create_instance(&mut self) {
+    fn create_instance(&mut self) -> Result<(), AppError> {
         let entry = self.entry.as_ref()
             .expect("Could not get `entry`.");
-        let window = self.window.as_ref()
-            .expect("Could not get `window`.");
-
         let instance = unsafe {
             let app_name = CString::new(APP_NAME).unwrap();
             let appinfo = vk::ApplicationInfo::builder()
@@ -184,19 +338,36 @@ impl App {
                 .application_version(0)
                 .api_version(vk::make_api_version(0, 1, 0, 0));
 
-            let layer_names = [CString::new("VK_LAYER_KHRONOS_validation").unwrap()];
-            let layers_names_raw: Vec<*const i8> = layer_names
-                .iter()
-                .map(|raw_name| raw_name.as_ptr())
-                .collect();
-    
-            let surface_extensions = ash_window::enumerate_required_extensions(window).unwrap();
+            let validation_layer = CString::new(VALIDATION_LAYER).unwrap();
+            let layers_names_raw: Vec<*const i8> = if self.validation {
+                let layer_present = entry
+                    .enumerate_instance_layer_properties()
+                    .map_err(AppError::NoDriver)?
+                    .iter()
+                    .any(|layer| CStr::from_ptr(layer.layer_name.as_ptr()) == validation_layer.as_c_str());
+                if !layer_present {
+                    return Err(AppError::MissingValidationLayer);
+                }
+
+                vec![validation_layer.as_ptr()]
+            } else {
+                vec![]
+            };
+
+            // a headless app draws without a surface
+            let surface_extensions = match self.window.as_ref() {
+                Some(window) => ash_window::enumerate_required_extensions(window)
+                    .map_err(AppError::MissingExtension)?,
+                None => &[],
+            };
             let extension_names_raw = {
                 let mut extension_names_raw = surface_extensions
                     .iter()
                     .map(|ext| *ext)
                     .collect::<Vec<_>>();
-                extension_names_raw.push(DebugUtils::name().as_ptr());
+                if self.validation {
+                    extension_names_raw.push(DebugUtils::name().as_ptr());
+                }
 
                 extension_names_raw
             };
@@ -208,21 +379,22 @@ impl App {
 
             entry
                 .create_instance(&create_info, None)
-                .expect("Failed to create `instance`")
+                .map_err(|e| match e {
+                    vk::Result::ERROR_EXTENSION_NOT_PRESENT => AppError::MissingExtension(e),
+                    e => AppError::NoDriver(e),
+                })?
         };
 
         self.instance = Some(instance);
+
+        Ok(())
     }
 
     fn init_debug_utils_loader(&mut self) {
-        use crate::helpers::vulkan_debug_callback;
+        use crate::helpers::{vulkan_debug_callback, vulkan_message_severities};
 
         let debug_info = vk::DebugUtilsMessengerCreateInfoEXT::builder()
-            .message_severity(
-                vk::DebugUtilsMessageSeverityFlagsEXT::ERROR
-                    | vk::DebugUtilsMessageSeverityFlagsEXT::WARNING
-                    | vk::DebugUtilsMessageSeverityFlagsEXT::INFO,
-            )
+            .message_severity(vulkan_message_severities())
             .message_type(
                 vk::DebugUtilsMessageTypeFlagsEXT::GENERAL
                     | vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION
@@ -247,7 +419,7 @@ impl App {
         self.debug_utils_messenger = Some(debug_utils_messenger);
     }
 
-    fn create_surface(&mut self) {
+    fn create_surface(&mut self) -> Result<(), AppError> {
         let entry = self.entry.as_ref()
             .expect("Could not get `entry`.");
         let instance = self.instance.as_ref()
@@ -256,16 +428,19 @@ impl App {
             .expect("Could not get `window`.");
 
         let surface = unsafe {
-            ash_window::create_surface(entry, instance, window, None).unwrap()
+            ash_window::create_surface(entry, instance, window, None)
+                .map_err(AppError::Surface)?
         };
 
         let surface_loader = Surface::new(entry, instance);
 
         self.surface = Some(surface);
         self.surface_loader = Some(surface_loader);
+
+        Ok(())
     }
     
-    fn pick_physical_device(&mut self) {
+    fn pick_physical_device(&mut self) -> Result<(), AppError> {
 
         let instance = self.instance.as_ref()
             .expect("Could not get instance");
@@ -273,50 +448,105 @@ impl App {
         let physical_devices = unsafe {
             instance
                 .enumerate_physical_devices()
-                .expect("Physical device error")
+                .map_err(|e| AppError::Vulkan("Listing the GPUs", e))?
         };
 
-        let surface = self.surface.as_ref()
-            .expect("Could not get `surface`");
-        let surface_loader = self.surface_loader.as_ref()
-            .expect("Could not get `surface_loader`.");
-
-        let (physical_device, queue_family_index) = unsafe {
+        // every device that can draw to the window, or at all when headless,
+        // with its index in `physical_devices`
+        let candidates: Vec<(usize, vk::PhysicalDevice, usize, vk::PhysicalDeviceProperties)> = unsafe {
             physical_devices
                 .iter()
-                .map(|physical_device| {
+                .enumerate()
+                .filter_map(|(device_index, physical_device)| {
                     instance
                         .get_physical_device_queue_family_properties(*physical_device)
                         .iter()
                         .enumerate()
                         .find_map(|(index, info)| {
+                            let supports_surface = match (self.surface.as_ref(), self.surface_loader.as_ref()) {
+                                (Some(surface), Some(surface_loader)) => surface_loader
+                                    .get_physical_device_surface_support(*physical_device, index as u32, *surface)
+                                    .unwrap_or(false),
+                                _ => true,
+                            };
                             let supports_graphic_and_surface =
-                                info.queue_flags.contains(vk::QueueFlags::GRAPHICS)
-                                    && surface_loader
-                                        .get_physical_device_surface_support(
-                                            *physical_device,
-                                            index as u32,
-                                            *surface,
-                                        )
-                                        .unwrap();
+                                info.queue_flags.contains(vk::QueueFlags::GRAPHICS) && supports_surface;
                             if supports_graphic_and_surface {
-                                Some((*physical_device, index))
+                                let properties = instance.get_physical_device_properties(*physical_device);
+                                Some((device_index, *physical_device, index, properties))
                             } else {
                                 None
                             }
                         })
                 })
-                .flatten()
-                .next()
-                .expect("Couldn't find suitable device.")
+                .collect()
+        };
+
+        let device_name = |properties: &vk::PhysicalDeviceProperties| unsafe {
+            CStr::from_ptr(properties.device_name.as_ptr()).to_string_lossy().into_owned()
+        };
+
+        for (device_index, _, _, properties) in candidates.iter() {
+            log::info!(
+                "GPU {}: {} ({:?}, score {})",
+                device_index,
+                device_name(properties),
+                properties.device_type,
+                device_type_score(properties.device_type),
+            );
+        }
+
+        let chosen = match self.device_override.as_ref() {
+            Some(wanted) => {
+                let wanted_lowercase = wanted.to_lowercase();
+                let device_type = match wanted_lowercase.as_str() {
+                    "discrete" => Some(vk::PhysicalDeviceType::DISCRETE_GPU),
+                    "integrated" => Some(vk::PhysicalDeviceType::INTEGRATED_GPU),
+                    "virtual" => Some(vk::PhysicalDeviceType::VIRTUAL_GPU),
+                    "cpu" => Some(vk::PhysicalDeviceType::CPU),
+                    _ => None,
+                };
+
+                // a number is only an index, "1" is also part of names like "GTX 1080"
+                let index: Option<usize> = wanted.parse().ok();
+
+                candidates
+                    .iter()
+                    .find(|(device_index, _, _, properties)| match (device_type, index) {
+                        (Some(device_type), _) => properties.device_type == device_type,
+                        (None, Some(index)) => *device_index == index,
+                        (None, None) => device_name(properties).to_lowercase().contains(&wanted_lowercase),
+                    })
+                    .ok_or_else(|| {
+                        let names: Vec<String> = candidates
+                            .iter()
+                            .map(|(device_index, _, _, properties)| format!("{} {}", device_index, device_name(properties)))
+                            .collect();
+                        AppError::DeviceNotFound(wanted.clone(), names.join(", "))
+                    })?
+            }
+            // the first of the best kind, in the order the loader lists them
+            None => candidates
+                .iter()
+                .rev()
+                .max_by_key(|(_, _, _, properties)| device_type_score(properties.device_type))
+                .ok_or(AppError::NoSuitableDevice)?,
         };
 
+        let (_, physical_device, queue_family_index, properties) = *chosen;
+        log::info!("Using GPU {}", device_name(&properties));
+
         self.physical_device = Some(physical_device);
         self.queue_family_index = Some(queue_family_index as u32);
+
+        Ok(())
     }
 
-    fn create_logical_device(&mut self) {
-        let device_extension_names_raw = [Swapchain::name().as_ptr()];
+    fn create_logical_device(&mut self) -> Result<(), AppError> {
+        let device_extension_names_raw = match self.surface {
+            Some(_) => vec![Swapchain::name().as_ptr()],
+            None => vec![],
+        };
         let features = vk::PhysicalDeviceFeatures {
             shader_clip_distance: 1,
             ..Default::default()
@@ -343,13 +573,33 @@ impl App {
         let device = unsafe {
             instance
                 .create_device(*physical_device, &device_create_info, None)
-                .unwrap()
+                .map_err(|e| AppError::Vulkan("Creating the logical device", e))?
         };
 
         self.device = Some(device);
+
+        Ok(())
     }
 
-    fn create_swapchain(&mut self) {
+    // The extent of the surface, or the size of the window when the surface follows it.
+    fn surface_extent(&self) -> Result<vk::Extent2D, AppError> {
+        let physical_device = self.physical_device.as_ref().unwrap();
+        let surface = self.surface.as_ref().unwrap();
+        let surface_loader = self.surface_loader.as_ref().unwrap();
+
+        let surface_capabilities = unsafe {
+            surface_loader
+                .get_physical_device_surface_capabilities(*physical_device, *surface)
+                .map_err(AppError::Surface)?
+        };
+
+        Ok(match surface_capabilities.current_extent.width {
+            std::u32::MAX => self.surface_resolution.unwrap(),
+            _ => surface_capabilities.current_extent,
+        })
+    }
+
+    fn create_swapchain(&mut self) -> Result<(), AppError> {
         let physical_device = self.physical_device.as_ref()
             .expect("Could not get `physical_device`.");
         let surface = self.surface.as_ref()
@@ -360,18 +610,15 @@ impl App {
         let surface_capabilities = unsafe {
             surface_loader
                 .get_physical_device_surface_capabilities(*physical_device, *surface)
-                .unwrap()
+                .map_err(AppError::Surface)?
         };
 
-        let surface_resolution = match surface_capabilities.current_extent.width {
-            std::u32::MAX => self.surface_resolution.unwrap(),
-            _ => surface_capabilities.current_extent,
-        };
+        let surface_resolution = self.surface_extent()?;
 
         let surface_format = unsafe {
             surface_loader
                 .get_physical_device_surface_formats(*physical_device, *surface)
-                .unwrap()[0]
+                .map_err(AppError::Surface)?[0]
         };
 
         let mut desired_image_count = surface_capabilities.min_image_count + 1;
@@ -391,14 +638,16 @@ impl App {
         let present_modes = unsafe {
             surface_loader
                 .get_physical_device_surface_present_modes(*physical_device, *surface)
-                .unwrap()
+                .map_err(AppError::Surface)?
         };
 
-        let present_mode = present_modes
+        let present_mode = self.settings.present_mode
+            .fallbacks()
             .iter()
             .cloned()
-            .find(|&mode| mode == vk::PresentModeKHR::MAILBOX)
+            .find(|mode| present_modes.contains(mode))
             .unwrap_or(vk::PresentModeKHR::FIFO);
+        self.present_mode = Some((self.settings.present_mode, present_mode));
         
         let instance = self.instance.as_ref()
             .expect("Could not get `instance`.");
@@ -407,13 +656,24 @@ impl App {
 
         let swapchain_loader = Swapchain::new(instance, device);
 
+        // capturing copies the rendered swapchain images
+        let capture_supported = surface_capabilities
+            .supported_usage_flags
+            .contains(vk::ImageUsageFlags::TRANSFER_SRC);
+        let image_usage = if capture_supported {
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC
+        } else {
+            vk::ImageUsageFlags::COLOR_ATTACHMENT
+        };
+        self.capture_supported = capture_supported;
+
         let swapchain_create_info = vk::SwapchainCreateInfoKHR::builder()
             .surface(*surface)
             .min_image_count(desired_image_count)
             .image_color_space(surface_format.color_space)
             .image_format(surface_format.format)
             .image_extent(surface_resolution)
-            .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
+            .image_usage(image_usage)
             .image_sharing_mode(vk::SharingMode::EXCLUSIVE)
             .pre_transform(pre_transform)
             .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
@@ -424,13 +684,15 @@ impl App {
         let swapchain = unsafe {
             swapchain_loader
                 .create_swapchain(&swapchain_create_info, None)
-                .unwrap()
+                .map_err(|e| AppError::Vulkan("Creating the swapchain", e))?
         };
 
         self.surface_format = Some(surface_format);
         self.surface_resolution = Some(surface_resolution);
         self.swapchain_loader = Some(swapchain_loader);
         self.swapchain = Some(swapchain);
+
+        Ok(())
     }
 
     fn create_image_views(&mut self) {
@@ -713,6 +975,100 @@ impl App {
         self.render_finished_semaphores = Some(render_finished_semaphores);
     }
 
+    fn destroy_sync_objects(&mut self) {
+        let device = self.device.as_ref().unwrap();
+
+        unsafe {
+            for render_finished_semaphore in self.render_finished_semaphores.take().unwrap() {
+                device.destroy_semaphore(render_finished_semaphore, None);
+            }
+
+            for image_available_semaphore in self.image_available_semaphores.take().unwrap() {
+                device.destroy_semaphore(image_available_semaphore, None);
+            }
+
+            for inflight_fence in self.inflight_fences.take().unwrap() {
+                device.destroy_fence(inflight_fence, None);
+            }
+        }
+    }
+
+    // Use the shaders that changed on disk if they make a pipeline, and keep the current ones otherwise.
+    fn reload_shaders(&mut self) {
+        let shaders = match self.shader_watcher.as_mut().and_then(|watcher| watcher.poll()) {
+            Some(shaders) => shaders,
+            None => return,
+        };
+
+        let pipeline = Pipeline::new(
+            self.device.as_ref().unwrap().clone(),
+            self.surface_format.as_ref().unwrap(),
+            self.surface_resolution.as_ref().unwrap(),
+            &self.descriptor_set_layouts.as_ref().unwrap()[0],
+            self.render_mode.unwrap().polygon_mode(),
+            &shaders,
+        );
+
+        match pipeline {
+            Ok(pipeline) => {
+                let device = self.device.as_ref().unwrap();
+
+                // the framebuffers are made for the render pass of the pipeline they're drawn with
+                unsafe {
+                    device.device_wait_idle().unwrap();
+
+                    for framebuffer in self.framebuffers.take().unwrap() {
+                        device.destroy_framebuffer(framebuffer, None);
+                    }
+                }
+
+                self.pipeline = Some(pipeline);
+                self.shaders = Some(shaders);
+                self.create_framebuffers();
+            }
+            Err(e) => self.shader_watcher.as_mut().unwrap().fail(e.to_string()),
+        }
+    }
+
+    // Apply the settings changed in the user interface, between frames.
+    fn apply_display_settings(&mut self) {
+        if self.present_mode.map(|(requested, _)| requested) != Some(self.settings.present_mode) {
+            self.swapchain_outdated = true;
+        }
+
+        if self.render_mode != Some(self.settings.render_mode) {
+            unsafe {
+                self.device.as_ref().unwrap().device_wait_idle().unwrap();
+            }
+
+            // the pipeline is rebuilt with the swapchain
+            self.render_mode = Some(self.settings.render_mode);
+            self.create_index_buffer();
+            self.swapchain_outdated = true;
+        }
+
+        let window = self.window.as_ref().unwrap();
+        if window.fullscreen().is_some() != self.settings.window.fullscreen {
+            window.set_fullscreen(self.settings.window.fullscreen.then(|| Fullscreen::Borderless(None)));
+        }
+
+        let viewports = self.viewports.as_mut().unwrap();
+        if *viewports.controls() != self.settings.controls {
+            viewports.set_controls(&self.settings.controls, &self.settings.keys);
+        }
+
+        if self.max_frames_in_flight != Some(self.settings.frames_in_flight) {
+            unsafe {
+                self.device.as_ref().unwrap().device_wait_idle().unwrap();
+            }
+
+            self.destroy_sync_objects();
+            self.max_frames_in_flight = Some(self.settings.frames_in_flight);
+            self.current_frame = Some(0);
+            self.create_sync_objects();
+        }
+    }
+
     fn create_graphic_queue(&mut self) {
         let device = self.device.as_ref().unwrap();
         let queue_family_index = self.queue_family_index.unwrap();
@@ -726,17 +1082,17 @@ impl App {
 
     fn create_descriptor_pool(&mut self) {
         let device = self.device.as_ref().unwrap();
-        let swapchain_image_count = self.swapchain_image_count.unwrap() as u32;
+        let set_count = (self.swapchain_image_count.unwrap() * MAX_VIEWS) as u32;
 
         let descriptor_pool_size = vk::DescriptorPoolSize::builder()
             .ty(vk::DescriptorType::UNIFORM_BUFFER)
-            .descriptor_count(swapchain_image_count);
+            .descriptor_count(set_count);
 
         let pool_sizes = &[descriptor_pool_size.build()];
 
         let descriptor_pool_create_info = vk::DescriptorPoolCreateInfo::builder()
             .pool_sizes(pool_sizes)
-            .max_sets(swapchain_image_count);
+            .max_sets(set_count);
 
         let descriptor_pool = unsafe {
             device.create_descriptor_pool(&descriptor_pool_create_info, None).unwrap()
@@ -749,7 +1105,7 @@ impl App {
     fn create_descriptor_set_layouts(&mut self) {
 
         let device = self.device.as_ref().unwrap();
-        let swapchain_image_count = self.swapchain_image_count.unwrap();
+        let set_count = self.swapchain_image_count.unwrap() * MAX_VIEWS;
 
         let ubo_layout_binding = vk::DescriptorSetLayoutBinding::builder()
             .binding(0)
@@ -761,10 +1117,10 @@ impl App {
         let layout_create_info = vk::DescriptorSetLayoutCreateInfo::builder()
             .bindings(layout_bindings);
 
-        let mut descriptor_set_layouts = Vec::with_capacity(swapchain_image_count);
+        let mut descriptor_set_layouts = Vec::with_capacity(set_count);
 
 
-        for _ in 0..swapchain_image_count {
+        for _ in 0..set_count {
             let descriptor_set_layout = unsafe {
                 device.create_descriptor_set_layout(&layout_create_info, None).unwrap()
             };
@@ -777,7 +1133,7 @@ impl App {
 
     fn create_descriptor_sets(&mut self) {
         let device = self.device.as_ref().unwrap();
-        let swapchain_image_count = self.swapchain_image_count.unwrap();
+        let set_count = self.swapchain_image_count.unwrap() * MAX_VIEWS;
         let uniform_buffers = self.uniform_buffers.as_ref().unwrap();
         let descriptor_pool = self.descriptor_pool.as_ref().unwrap();
         let set_layouts = self.descriptor_set_layouts.as_ref().unwrap();
@@ -790,7 +1146,7 @@ impl App {
             device.allocate_descriptor_sets(&allocate_info).unwrap()
         };
 
-        for i in 0..swapchain_image_count {
+        for i in 0..set_count {
             let descriptor_buffer_info = vk::DescriptorBufferInfo::builder()
                 .offset(0)
                 .buffer(*uniform_buffers[i].buffer())
@@ -815,15 +1171,20 @@ impl App {
 
     }
 
-    fn create_pipeline(&mut self) {
+    fn create_pipeline(&mut self) -> Result<(), AppError> {
         let device = self.device.as_ref().unwrap();
         let surface_format = self.surface_format.as_ref().unwrap();
         let surface_resolution = self.surface_resolution.as_ref().unwrap();
         let descriptor_set_layout = &self.descriptor_set_layouts.as_ref().unwrap()[0];
 
-        let pipeline = Pipeline::new(device.clone(), surface_format, surface_resolution, descriptor_set_layout);
+        let polygon_mode = self.render_mode.unwrap().polygon_mode();
+        let shaders = self.shaders.as_ref().unwrap();
+
+        let pipeline = Pipeline::new(device.clone(), surface_format, surface_resolution, descriptor_set_layout, polygon_mode, shaders)?;
 
         self.pipeline = Some(pipeline);
+
+        Ok(())
     }
 
     fn create_framebuffers(&mut self) {
@@ -856,16 +1217,34 @@ impl App {
         self.framebuffers = Some(framebuffers);
     }
 
-    fn create_entities(&mut self) {
-        let entities = vec![Entity::hypercube()];
+    fn create_entities(&mut self) -> Result<(), AppError> {
+        let entity = match &self.settings.scene {
+            Some(path) => Entity::load(path)
+                .map_err(|reason| AppError::Scene { path: path.clone(), reason })?,
+            None => Entity::hypercube(),
+        };
+
+        self.inspector.analyze(&entity);
+        for check in self.inspector.failed() {
+            log::warn!("The scene fails the check {}: {}", check.name, check.value);
+        }
+
+        self.entities = Some(vec![entity]);
+
+        Ok(())
+    }
+
+    fn create_unfolding(&mut self) {
+        let entity = &self.entities.as_ref().unwrap()[0];
 
-        self.entities = Some(entities);
+        self.unfolding = Unfolding::new(entity);
     }
 
     fn create_camera(&mut self) {
-        let camera = Camera::new();
+        let mut viewports = Viewports::new();
+        viewports.set_controls(&self.settings.controls, &self.settings.keys);
 
-        self.camera = Some(camera);
+        self.viewports = Some(viewports);
     }
 
     fn create_vertex_buffers(&mut self) {
@@ -927,8 +1306,11 @@ impl App {
         let entities = self.entities.as_ref().unwrap();
         let entity = &entities[0];
 
-        let indices = entity.indices();
-        let buffer_size = std::mem::size_of::<u16>() as u64 * indices.len() as u64;
+        let indices = match self.render_mode.unwrap() {
+            RenderMode::Wireframe => entity.indices(),
+            RenderMode::Solid => entity.topology().triangle_indices(),
+        };
+        let buffer_size = std::mem::size_of::<u32>() as u64 * indices.len() as u64;
 
         let mut staging_buffer = Buffer::new(
             device.clone(),
@@ -951,14 +1333,15 @@ impl App {
         vertex_buffer.transform_from(&queue, &command_buffer ,&staging_buffer);
 
         self.index_buffers = Some(vec![vertex_buffer]);
+        self.index_count = indices.len() as u32;
     }
 
     fn create_uniform_buffers(&mut self) {
         let device = self.device.as_ref().unwrap();
         let allocator = self.allocator.as_ref().unwrap();
-        let present_image_size = self.swapchain_image_count.unwrap();
+        let present_image_size = self.swapchain_image_count.unwrap() * MAX_VIEWS;
 
-        let camera = self.camera.as_ref().unwrap();
+        let camera = self.viewports.as_ref().unwrap().active();
         let entity = &self.entities.as_ref().unwrap()[0];
         let ubo = camera.data(&entity.transform());
 
@@ -988,19 +1371,7 @@ impl App {
                 MemoryLocation::CpuToGpu
             );
 
-            let data = {
-                [
-                    ubo.cam4_trans.as_slice(),
-                    ubo.cam4_col.as_slice(),
-                    ubo.cam4_row.as_slice(),
-                    ubo.cam3_trans.as_slice(),
-                    &[ubo.cam4_const]
-                ]
-                .concat()
-            };
-
-            // uniform_buffer.set_data(&vec![ubo]);
-            uniform_buffer.set_data(&data);
+            uniform_buffer.set_data(&vec![ubo]);
 
             uniform_buffers.push(uniform_buffer);
         }
@@ -1058,53 +1429,136 @@ impl App {
         self.egui_integration = Some(egui_integration);
     }
 
-    fn update_uniform_buffer(&mut self) {
-        let camera = self.camera.as_mut().unwrap();
+    // Update the cameras for an image of size `extent`.
+    fn update_uniform_buffer(&mut self, extent: vk::Extent2D) {
+        let viewports = self.viewports.as_mut().unwrap();
         let entity = &self.entities.as_ref().unwrap()[0];
+        let clock = self.clock.as_ref().unwrap();
 
-        camera.update_view();
-        let ubo = camera.data(&entity.transform());
+        let aspect = extent.width as f32 / extent.height as f32;
+        viewports.update_view(clock.dt(), aspect);
 
+        let views = viewports.views(&self.stereo);
         let uniform_buffers = self.uniform_buffers.as_mut().unwrap();
 
-        for uniform_buffer in uniform_buffers {
-            uniform_buffer.set_data(&vec![ubo]);
+        for slots in uniform_buffers.chunks_mut(MAX_VIEWS) {
+            for (uniform_buffer, (index, eye)) in slots.iter_mut().zip(views.iter()) {
+                let camera = viewports.camera(*index);
+                let mut ubo = camera.data(&entity.transform());
+                ubo.cam3_trans = camera.camera3.eye_transform(eye.offset, eye.aspect_scale);
+
+                uniform_buffer.set_data(&vec![ubo]);
+            }
+        }
+    }
+
+    fn update_animation(&mut self) {
+        let time = self.clock.as_ref().unwrap().time();
+
+        for entity in self.entities.as_mut().unwrap().iter_mut() {
+            entity.animate(time);
+        }
+    }
+
+    // Rebuild the unfolded entity and its buffers when the unfold slider has moved.
+    fn update_unfolding(&mut self) {
+        let unfolding = match self.unfolding.as_ref() {
+            Some(unfolding) => unfolding,
+            None => return,
+        };
+
+        if self.unfold_amount == self.unfolded_amount {
+            return;
+        }
+
+        let mut entity = unfolding.entity(self.unfold_amount);
+        let previous = &self.entities.as_ref().unwrap()[0];
+        entity.set_pose(previous.pose());
+        entity.set_animation(previous.animation().cloned());
+
+        // the net has its own vertex and cell numbering
+        if entity.vertices().len() != self.entities.as_ref().unwrap()[0].vertices().len() {
+            self.selection = None;
         }
+
+        self.inspector.analyze(&entity);
+        self.entities.as_mut().unwrap()[0] = entity;
+        self.unfolded_amount = self.unfold_amount;
+
+        unsafe {
+            self.device.as_ref().unwrap().device_wait_idle().unwrap();
+        }
+
+        self.create_vertex_buffers();
+        self.create_index_buffer();
     }
 
+    // Replace the entity with the scene picked in the side panel.
+    fn update_scene(&mut self) {
+        let entity = match self.scenes.take_request() {
+            Some(entity) => entity,
+            None => return,
+        };
+
+        self.inspector.analyze(&entity);
+        self.entities = Some(vec![entity]);
+        self.selection = None;
+        self.gizmo = Gizmo::default();
+
+        self.create_unfolding();
+        self.unfold_amount = 0.0;
+        self.unfolded_amount = 0.0;
+
+        unsafe {
+            self.device.as_ref().unwrap().device_wait_idle().unwrap();
+        }
+
+        self.create_vertex_buffers();
+        self.create_index_buffer();
+    }
+
+    // Parts are missing in a headless app, and after a recreation that stopped halfway.
     fn cleanup_swapchain(&mut self) {
         let device = self.device.as_ref().unwrap();
-        let swapchain_loader = self.swapchain_loader.as_ref().unwrap();
         let command_pool = self.command_pool.as_ref().unwrap();
         let allocator = self.allocator.as_ref().unwrap();
 
-        let depth_image_allocation = self.depth_image_allocation.take().unwrap();
         unsafe {
             device.device_wait_idle().unwrap();
 
-            device.destroy_image(self.depth_image.take().unwrap(), None);
-            device.destroy_image_view(self.depth_image_view.take().unwrap(), None);
-            allocator.lock().unwrap().free(depth_image_allocation).unwrap();
+            if let Some(depth_image) = self.depth_image.take() {
+                device.destroy_image(depth_image, None);
+            }
+            if let Some(depth_image_view) = self.depth_image_view.take() {
+                device.destroy_image_view(depth_image_view, None);
+            }
+            if let Some(depth_image_allocation) = self.depth_image_allocation.take() {
+                allocator.lock().unwrap().free(depth_image_allocation).unwrap();
+            }
 
-            device.free_command_buffers(*command_pool, self.draw_command_buffers.take().unwrap().as_slice());
+            if let Some(draw_command_buffers) = self.draw_command_buffers.take() {
+                device.free_command_buffers(*command_pool, draw_command_buffers.as_slice());
+            }
             
-            for framebuffer in self.framebuffers.take().unwrap() {
+            for framebuffer in self.framebuffers.take().into_iter().flatten() {
                 device.destroy_framebuffer(framebuffer, None);
             }
 
-            drop(self.pipeline.take().unwrap());
+            drop(self.pipeline.take());
 
-            for image_view in self.swapchain_image_views.take().unwrap() {
+            for image_view in self.swapchain_image_views.take().into_iter().flatten() {
                 device.destroy_image_view(image_view, None);
             }
 
-            swapchain_loader.destroy_swapchain(self.swapchain.take().unwrap(), None);
-
-            for uniform_buffer in self.uniform_buffers.take().unwrap() {
-                drop(uniform_buffer);
+            if let (Some(swapchain_loader), Some(swapchain)) = (self.swapchain_loader.as_ref(), self.swapchain.take()) {
+                swapchain_loader.destroy_swapchain(swapchain, None);
             }
 
-            device.destroy_descriptor_pool(self.descriptor_pool.take().unwrap(), None);
+            drop(self.uniform_buffers.take());
+
+            if let Some(descriptor_pool) = self.descriptor_pool.take() {
+                device.destroy_descriptor_pool(descriptor_pool, None);
+            }
         }
     }
 
@@ -1112,7 +1566,22 @@ impl App {
         self.surface_resolution = Some(surface_resolution);
     }
 
-    pub fn recreate_swapchain(&mut self) {
+    // Stays outdated while the window is minimized or the surface changes under it,
+    // then it's retried before the next frame.
+    pub fn recreate_swapchain(&mut self) -> Result<(), AppError> {
+        self.swapchain_outdated = true;
+
+        // a minimized window has nothing to present to, wait until it's restored
+        let surface_resolution = self.surface_resolution.unwrap();
+        if surface_resolution.width == 0 || surface_resolution.height == 0 {
+            return Ok(());
+        }
+        // the size from the window events can lag behind the surface
+        let surface_extent = self.surface_extent()?;
+        if surface_extent.width == 0 || surface_extent.height == 0 {
+            return Ok(());
+        }
+
         let device = self.device.as_ref().unwrap();
 
         unsafe {
@@ -1121,19 +1590,26 @@ impl App {
 
         self.cleanup_swapchain();
 
-        self.create_swapchain();
+        match self.create_swapchain() {
+            Ok(()) => (),
+            Err(AppError::Vulkan(_, vk::Result::ERROR_OUT_OF_DATE_KHR)) => return Ok(()),
+            Err(e) => return Err(e),
+        }
         self.create_image_views();
         self.create_uniform_buffers();
 
         self.create_descriptor_pool();
         self.create_descriptor_sets();
 
-        self.create_pipeline();
+        self.create_pipeline()?;
         self.create_depth_resource();
 
         self.create_framebuffers();
         self.create_draw_command_buffers();
 
+        // the new swapchain may have a different number of images
+        self.images_inflight = Some(vec![vk::Fence::null(); self.swapchain_image_count.unwrap()]);
+
         let egui_integration = self.egui_integration.as_mut().unwrap();
         let surface_resolution = self.surface_resolution.as_ref().unwrap();
         let swapchain = self.swapchain.as_ref().unwrap();
@@ -1146,6 +1622,8 @@ impl App {
             surface_format.clone(),
         );
 
+        self.swapchain_outdated = false;
+        Ok(())
     }
 
     pub fn egui_integration_handle_event(&mut self, event: &winit::event::WindowEvent) {
@@ -1153,13 +1631,260 @@ impl App {
         egui_integration.handle_event(event);
     }
 
-    pub fn render(&mut self) {
+    // Record the scene render pass into `framebuffer`, covering `extent`,
+    // with the uniform buffers of swapchain image `image_index`.
+    fn record_scene(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        framebuffer: vk::Framebuffer,
+        extent: vk::Extent2D,
+        image_index: usize,
+    ) {
+        let device = self.device.as_ref().unwrap();
+        let pipeline = self.pipeline.as_ref().unwrap();
+        let descriptor_sets = self.descriptor_sets.as_ref().unwrap();
+        let render_pass = pipeline.render_pass();
+        let pipeline_layout = pipeline.pipeline_layout();
+        let vertex_buffers = self.vertex_buffers.as_ref().unwrap();
+        let index_buffers = self.index_buffers.as_ref().unwrap();
+        let index_count = self.index_count;
+
+        let clear_values = [
+            vk::ClearValue {
+                color: vk::ClearColorValue {
+                    float32: [0.0, 0.0, 0.0, 1.0],
+                },
+            },
+            vk::ClearValue {
+                depth_stencil: vk::ClearDepthStencilValue {
+                    depth: 1.0,
+                    stencil: 0,
+                },
+            },
+        ];
+
+        let render_pass_begin_info = vk::RenderPassBeginInfo::builder()
+            .render_pass(*render_pass)
+            .framebuffer(framebuffer)
+            .render_area(vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent,
+            })
+            .clear_values(&clear_values);
+
+        unsafe {
+            device.cmd_begin_render_pass(
+                command_buffer,
+                &render_pass_begin_info,
+                vk::SubpassContents::INLINE,
+            );
+            device.cmd_bind_vertex_buffers(
+                command_buffer,
+                0,
+                &[*vertex_buffers[0].buffer()],
+                &[0],
+            );
+            device.cmd_bind_index_buffer(
+                command_buffer,
+                *index_buffers[0].buffer(),
+                0,
+                vk::IndexType::UINT32,
+            );
+
+            let views = self.viewports.as_ref().unwrap().views(&self.stereo);
+            for (view, (_, eye)) in views.iter().enumerate() {
+                let [x, y, width, height] = eye.area;
+                let scissor = vk::Rect2D {
+                    offset: vk::Offset2D {
+                        x: (x * extent.width as f32) as i32,
+                        y: (y * extent.height as f32) as i32,
+                    },
+                    extent: vk::Extent2D {
+                        width: (width * extent.width as f32) as u32,
+                        height: (height * extent.height as f32) as u32,
+                    },
+                };
+                let viewport = vk::Viewport {
+                    x: scissor.offset.x as f32,
+                    y: scissor.offset.y as f32,
+                    width: scissor.extent.width as f32,
+                    height: scissor.extent.height as f32,
+                    min_depth: 0.0,
+                    max_depth: 1.0,
+                };
+
+                // an anaglyph draws both eyes over each other,
+                // and the depth of the last view must not hide the next
+                if view > 0 {
+                    let clear_depth = vk::ClearAttachment {
+                        aspect_mask: vk::ImageAspectFlags::DEPTH,
+                        color_attachment: 0,
+                        clear_value: clear_values[1],
+                    };
+                    let clear_rect = vk::ClearRect {
+                        rect: scissor,
+                        base_array_layer: 0,
+                        layer_count: 1,
+                    };
+                    device.cmd_clear_attachments(command_buffer, &[clear_depth], &[clear_rect]);
+                }
+
+                device.cmd_bind_pipeline(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    *pipeline.masked_pipeline(eye.mask),
+                );
+                device.cmd_set_viewport(command_buffer, 0, &[viewport]);
+                device.cmd_set_scissor(command_buffer, 0, &[scissor]);
+
+                device.cmd_bind_descriptor_sets(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    *pipeline_layout,
+                    0,
+                    &[descriptor_sets[image_index * MAX_VIEWS + view]],
+                    &[],
+                );
+
+                device.cmd_draw_indexed(
+                    command_buffer,
+                    index_count,
+                    1,
+                    0,
+                    0,
+                    1,
+                );
+            }
+
+            device.cmd_end_render_pass(command_buffer);
+        }
+    }
+
+    // Render the scene offscreen at `screenshot.scale` times the window size and save it.
+    fn take_screenshot(&mut self) {
+        let surface_resolution = self.surface_resolution.as_ref().unwrap();
+        let format = self.surface_format.as_ref().unwrap().format;
+
+        // any scale keeps the aspect of the window, which the uniform buffers were made for
+        let scale = |size: u32| ((size as f32 * self.screenshot.scale).round() as u32).max(1);
+        let extent = vk::Extent2D {
+            width: scale(surface_resolution.width),
+            height: scale(surface_resolution.height),
+        };
+
+        match self.render_offscreen(extent) {
+            Ok(buffer) => self.screenshot.save(buffer.data(), extent, format),
+            Err(error) => self.screenshot.fail(error),
+        }
+    }
+
+    // Render one frame at the start of the clock offscreen and save it to `path`.
+    pub fn render_still(&mut self, path: &Path, width: u32, height: u32) -> Result<(), AppError> {
+        let extent = vk::Extent2D { width, height };
+        let format = self.surface_format.as_ref().unwrap().format;
+
+        self.update_animation();
+        self.update_uniform_buffer(extent);
+
+        let buffer = self.render_offscreen(extent).map_err(AppError::Render)?;
+
+        save_readback(path, buffer.data(), extent, format).map_err(AppError::Render)
+    }
+
+    // Render the scene with the current uniform buffers into an image of size `extent`,
+    // and read it back.
+    fn render_offscreen(&self, extent: vk::Extent2D) -> Result<Buffer, String> {
+        let instance = self.instance.as_ref().unwrap();
+        let physical_device = self.physical_device.as_ref().unwrap();
+        let device = self.device.as_ref().unwrap();
+        let allocator = self.allocator.as_ref().unwrap();
+        let surface_format = self.surface_format.as_ref().unwrap();
+        let pipeline = self.pipeline.as_ref().unwrap();
+
+        let limits = unsafe {
+            instance.get_physical_device_properties(*physical_device).limits
+        };
+
+        if extent.width == 0 || extent.height == 0 {
+            return Err("The image is empty.".to_string());
+        }
+
+        if extent.width > limits.max_framebuffer_width || extent.height > limits.max_framebuffer_height {
+            return Err(format!(
+                "{}x{} is larger than the device limit of {}x{}.",
+                extent.width, extent.height, limits.max_framebuffer_width, limits.max_framebuffer_height,
+            ));
+        }
+
+        let offscreen = Offscreen::new(
+            device.clone(),
+            Arc::clone(allocator),
+            *pipeline.render_pass(),
+            surface_format.format,
+            extent,
+        );
+        let buffer = Buffer::new(
+            device.clone(),
+            extent.width as u64 * extent.height as u64 * 4,
+            vk::BufferUsageFlags::TRANSFER_DST,
+            Arc::clone(allocator),
+            MemoryLocation::GpuToCpu,
+        );
+        self.single_time_command(|device, command_buffer| {
+            self.record_scene(command_buffer, offscreen.framebuffer(), extent, 0);
+            record_readback(
+                device,
+                command_buffer,
+                offscreen.image(),
+                vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                offscreen.extent(),
+                &buffer,
+            );
+        });
+
+        Ok(buffer)
+    }
+
+    pub fn render(&mut self) -> Result<(), AppError> {
         let surface_resolution = self.surface_resolution.as_ref().unwrap();
         if surface_resolution.width == 0 || surface_resolution.height == 0 {
-            return;
+            return Ok(());
+        }
+
+        self.reload_shaders();
+
+        if self.swapchain_outdated {
+            self.recreate_swapchain()?;
+        }
+        // there is no swapchain to draw to until the surface settles
+        if self.swapchain_outdated {
+            return Ok(());
         }
 
-        self.update_uniform_buffer();
+        self.clock.as_mut().unwrap().tick();
+        self.update_scene();
+        self.update_unfolding();
+        self.update_animation();
+        self.update_uniform_buffer(self.surface_resolution.unwrap());
+
+        // the user interface can only be saved with the presented image
+        let mut overlay_screenshot = None;
+        if self.screenshot.take_request() {
+            if !self.screenshot.overlay {
+                self.take_screenshot();
+            } else if self.capture_supported {
+                let surface_resolution = self.surface_resolution.unwrap();
+                overlay_screenshot = Some(Buffer::new(
+                    self.device.as_ref().unwrap().clone(),
+                    surface_resolution.width as u64 * surface_resolution.height as u64 * 4,
+                    vk::BufferUsageFlags::TRANSFER_DST,
+                    Arc::clone(self.allocator.as_ref().unwrap()),
+                    MemoryLocation::GpuToCpu,
+                ));
+            } else {
+                self.screenshot.fail("The surface doesn't support capturing.".to_string());
+            }
+        }
 
         let device = self.device.as_ref().unwrap();
         let present_queue = self.present_queue.as_ref().unwrap();
@@ -1172,76 +1897,38 @@ impl App {
         let render_finished_semaphores = self.render_finished_semaphores.as_ref().unwrap();
         let image_available_semaphores = self.image_available_semaphores.as_ref().unwrap();
         let inflight_fences = self.inflight_fences.as_ref().unwrap();
-        let images_inflight = self.images_inflight.as_mut().unwrap();
 
         unsafe {
             device
                 .wait_for_fences(&[inflight_fences[current_frame]], true, std::u64::MAX)
                 .expect("Wait for fence failed.");
 
-            let (image_index, _) = swapchain_loader
+            let acquired = swapchain_loader
                 .acquire_next_image(
                     *swapchain,
                     std::u64::MAX,
                     image_available_semaphores[current_frame],
                     vk::Fence::null(),
-                )
-                .expect("Failed to acquire swapchain image.");
-            
-            // record draw commands
-            let clear_values = [
-                vk::ClearValue {
-                    color: vk::ClearColorValue {
-                        float32: [0.0, 0.0, 0.0, 1.0],
-                    },
-                },
-                vk::ClearValue {
-                    depth_stencil: vk::ClearDepthStencilValue {
-                        depth: 1.0,
-                        stencil: 0,
-                    },
-                },
-            ];
+                );
 
-            let descriptor_sets = self.descriptor_sets.as_ref().unwrap();
-            let pipeline = self.pipeline.as_ref().unwrap();
-            let graphics_pipeline = pipeline.pipeline();
-            let render_pass = pipeline.render_pass();
-            let pipeline_layout = pipeline.pipeline_layout();
-            let framebuffers = self.framebuffers.as_ref().unwrap();
-            let surface_resolution = self.surface_resolution.as_ref().unwrap();
-            let device = self.device.as_ref().unwrap();
-            let vertex_buffers = self.vertex_buffers.as_ref().unwrap();
-            let index_buffers = self.index_buffers.as_ref().unwrap();
-            let entities = self.entities.as_ref().unwrap();
-            let index_count = entities[0].indices().len() as u32;
+            // a suboptimal swapchain can still be presented to, it's recreated after this frame
+            let image_index = match acquired {
+                Ok((image_index, suboptimal)) => {
+                    self.swapchain_outdated |= suboptimal;
+                    image_index
+                }
+                Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                    if overlay_screenshot.is_some() {
+                        self.screenshot.request();
+                    }
+                    return self.recreate_swapchain();
+                }
+                Err(e) => panic!("Failed to acquire swapchain image: {}", e),
+            };
 
             let command_buffer = draw_command_buffers[image_index as usize];
-
-            let render_pass_begin_info = vk::RenderPassBeginInfo::builder()
-                .render_pass(*render_pass)
-                .framebuffer(framebuffers[image_index as usize])
-                .render_area(vk::Rect2D {
-                    offset: vk::Offset2D { x: 0, y: 0 },
-                    extent: *surface_resolution,
-                })
-                .clear_values(&clear_values);
-
-            // recreate viewports and scissors
-            // TODO remove these code
-            let viewports = [vk::Viewport {
-                x: 0.0,
-                y: 0.0,
-                width: surface_resolution.width as f32,
-                height: surface_resolution.height as f32,
-                min_depth: 0.0,
-                max_depth: 1.0,
-            }];
-
-            let scissors = [vk::Rect2D {
-                offset: vk::Offset2D { x: 0, y: 0 },
-                extent: *surface_resolution,
-            }];
+            let framebuffers = self.framebuffers.as_ref().unwrap();
+            let surface_resolution = self.surface_resolution.as_ref().unwrap();
 
             device
                 .reset_command_buffer(
@@ -1255,64 +1942,68 @@ impl App {
             device
                 .begin_command_buffer(command_buffer, &command_buffer_begin_info)
                 .expect("Begin commandbuffer");
-                
-            device.cmd_begin_render_pass(
-                command_buffer,
-                &render_pass_begin_info,
-                vk::SubpassContents::INLINE,
-            );
-            device.cmd_bind_pipeline(
-                command_buffer,
-                vk::PipelineBindPoint::GRAPHICS,
-                *graphics_pipeline,
-            );
-
-            device.cmd_set_viewport(command_buffer, 0, &viewports);
-            device.cmd_set_scissor(command_buffer, 0, &scissors);
-            device.cmd_bind_vertex_buffers(
-                command_buffer,
-                0,
-                &[*vertex_buffers[0].buffer()],
-                &[0],
-            );
-            device.cmd_bind_index_buffer(
-                command_buffer,
-                *index_buffers[0].buffer(),
-                0,
-                vk::IndexType::UINT16,
-            );
 
-            device.cmd_bind_descriptor_sets(
+            self.record_scene(
                 command_buffer,
-                vk::PipelineBindPoint::GRAPHICS,
-                *pipeline_layout,
-                0,
-                &[descriptor_sets[image_index as usize]],
-                &[],
+                framebuffers[image_index as usize],
+                *surface_resolution,
+                image_index as usize,
             );
 
-            device.cmd_draw_indexed(
+            // capture the scene before the user interface is drawn over it
+            self.recorder.record(
+                device,
+                self.allocator.as_ref().unwrap(),
                 command_buffer,
-                index_count,
-                1,
-                0,
-                0,
-                1,
+                self.swapchain_images.as_ref().unwrap()[image_index as usize],
+                *surface_resolution,
+                self.clock.as_ref().unwrap(),
             );
 
-            device.cmd_end_render_pass(command_buffer);
-
             // self.egui_ui(command_buffer, image_index);
             ///////////////////////////////////// egui /////////////////////////////////
             let egui_integration = self.egui_integration.as_mut().unwrap();
             let window = self.window.as_ref().unwrap();
 
-            let camera = self.camera.as_ref().unwrap();
-
+            let viewports = self.viewports.as_mut().unwrap();
+            let scenes = &mut self.scenes;
+            let unfolding = self.unfolding.as_ref();
+            let unfold_amount = &mut self.unfold_amount;
+            let labels = &mut self.labels;
+            let inspector = &mut self.inspector;
+            let shader_watcher = self.shader_watcher.as_ref();
+            let entities = self.entities.as_ref().unwrap();
+            let entity = &entities[0];
+            let selection = self.selection.as_ref();
+            let cursor_grabbed = self.cursor_grabbed;
+            let gizmo = &mut self.gizmo;
+
+            let before = selection.map(|selection| entities[selection.entity].pose());
+            let mut pose = before;
+            let clock = self.clock.as_mut().unwrap();
+            let recorder = &mut self.recorder;
+            let screenshot = &mut self.screenshot;
+            let stereo = &mut self.stereo;
+            let settings = &mut self.settings;
+            let present_mode = self.present_mode.map(|(_, present_mode)| present_mode);
+            let capture_supported = self.capture_supported;
+            let mut animation = selection.map(|selection| entities[selection.entity].animation().cloned());
+            let animation_before = animation.clone();
 
             egui_integration.begin_frame(window);
             egui::SidePanel::left("my_side_panel").show(&egui_integration.context(), |ui| {
+                ui.heading("Scene");
+                scenes.ui(ui);
+
+                ui.separator();
+                ui.heading("Viewports");
+                viewports.ui(ui);
+
+                ui.separator();
                 ui.heading("Camera4");
+                viewports.active_mut().projection_ui(ui);
+
+                let camera = viewports.active();
 
                 ui.label(format!("position: {:?}", camera.position()));
                 ui.label(format!("w: {:?}", camera.w()));
@@ -1322,32 +2013,125 @@ impl App {
 
                 ui.separator();
                 ui.heading("Camera3");
+                viewports.active_mut().camera3.projection_ui(ui);
+
+                let camera = viewports.active();
+
                 ui.label(format!("position: {:?}", camera.camera3.position()));
                 ui.label(format!("look at: {:?}", camera.camera3.look_at()));
                 ui.label(format!("look direction: {:?}", camera.camera3.look_direction()));
                 ui.label(format!("right direction: {:?}", camera.camera3.right_direction()));
 
+                if unfolding.is_some() {
+                    ui.separator();
+                    ui.heading("Unfold");
+                    ui.add(egui::Slider::new(unfold_amount, 0.0..=1.0).text("amount"));
+                }
+
+                ui.separator();
+                ui.heading("Inspector");
+                inspector.summary_ui(ui);
+
                 ui.separator();
-                ui.heading("Hyper Cube");
-                for v in self.entities.as_ref().unwrap()[0].vertices().iter() {
-                    let ve = na::Vector5::new(v.pos[0], v.pos[1], v.pos[2], v.pos[3], 1.0);
-                    let mut tmp = camera.transform() * ve;
-                    tmp = tmp / tmp[4];
+                ui.heading("Labels");
+                labels.settings_ui(ui, entity);
+
+                ui.separator();
+                ui.heading("Selection");
+                match selection {
+                    Some(selection) => selection.ui(ui, entities),
+                    None if cursor_grabbed => { ui.label("Press Tab to free the cursor, then click to select."); }
+                    None => { ui.label("Click a vertex, edge or cell to select it."); }
+                }
 
-                    ui.label(format!("{:?}", tmp));
+                if let Some(pose) = pose.as_mut() {
+                    ui.separator();
+                    ui.heading("Transform");
+                    gizmo.fields_ui(ui, pose);
+                }
+
+                ui.separator();
+                ui.heading("Stereo");
+                stereo.ui(ui);
+
+                ui.separator();
+                ui.heading("Display");
+                settings.display_ui(ui, present_mode);
+
+                ui.separator();
+                ui.heading("Controls");
+                settings.controls_ui(ui);
+
+                if let Some(shader_watcher) = shader_watcher {
+                    ui.separator();
+                    ui.heading("Shaders");
+                    shader_watcher.ui(ui);
+                }
+
+                ui.separator();
+                ui.heading("Clock");
+                clock_ui(ui, clock);
+
+                ui.separator();
+                ui.heading("Capture");
+                recorder.ui(ui, clock, capture_supported);
+
+                ui.separator();
+                ui.heading("Screenshot");
+                screenshot.ui(ui, capture_supported);
+
+                ui.separator();
+                ui.heading("Animation");
+                if let (Some(animation), Some(pose)) = (animation.as_mut(), pose.as_ref()) {
+                    animation_ui(ui, animation, pose);
                 }
             });
+            let camera = viewports.active();
+            let screen = viewports.active_rect(egui_integration.context().input().screen_rect());
+            viewports.paint(&egui_integration.context());
+            inspector.ui(&egui_integration.context());
+            labels.paint(&egui_integration.context(), camera, entity, screen);
+            if let Some(selection) = selection {
+                selection.paint(&egui_integration.context(), camera, entities, screen);
+            }
+            if let Some(pose) = pose.as_mut() {
+                gizmo.handles(&egui_integration.context(), camera, pose, screen);
+            }
+
+            let edited = selection.zip(before).zip(pose).map(|((selection, before), after)| (selection.entity, before, after));
+            if let Some((index, pose)) = gizmo.finish(&egui_integration.context(), edited) {
+                if let Some(entity) = self.entities.as_mut().unwrap().get_mut(index) {
+                    entity.set_pose(pose);
+                }
+            }
+            if let (Some(selection), Some(animation)) = (selection, animation) {
+                if Some(&animation) != animation_before.as_ref() {
+                    self.entities.as_mut().unwrap()[selection.entity].set_animation(animation);
+                }
+            }
             let output = egui_integration.end_frame(window);
 
             let clipped_meshes = egui_integration.context().tessellate(output.shapes);
             egui_integration
                 .paint(command_buffer, image_index as usize, clipped_meshes, output.textures_delta);
+
+            if let Some(buffer) = overlay_screenshot.as_ref() {
+                record_readback(
+                    device,
+                    command_buffer,
+                    self.swapchain_images.as_ref().unwrap()[image_index as usize],
+                    vk::ImageLayout::PRESENT_SRC_KHR,
+                    *surface_resolution,
+                    buffer,
+                );
+            }
             ///////////////////////////////////// egui /////////////////////////////////
 
             device
                 .end_command_buffer(command_buffer)
                 .expect("End commandbuffer");
 
+            let images_inflight = self.images_inflight.as_mut().unwrap();
             if vk::Fence::null() != images_inflight[image_index as usize] {
                 let fences = &[images_inflight[image_index as usize]];
 
@@ -1388,83 +2172,177 @@ impl App {
                 .swapchains(swapchains)
                 .image_indices(image_indices);
 
-            swapchain_loader
-                .queue_present(*present_queue, &present_info)
-                .expect("Failed to present swapchain image.");
+            match swapchain_loader.queue_present(*present_queue, &present_info) {
+                Ok(suboptimal) => self.swapchain_outdated |= suboptimal,
+                Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => self.swapchain_outdated = true,
+                Err(e) => panic!("Failed to present swapchain image: {}", e),
+            }
+
+            let surface_format = self.surface_format.as_ref().unwrap();
+            self.recorder.finish(device, inflight_fences[current_frame], surface_format.format);
+
+            if let Some(buffer) = overlay_screenshot {
+                device
+                    .wait_for_fences(&[inflight_fences[current_frame]], true, u64::MAX)
+                    .expect("Wait for fence failed.");
+                self.screenshot.save(buffer.data(), *surface_resolution, surface_format.format);
+            }
         }
 
         self.current_frame = Some((current_frame + 1) % max_frames_in_flight);
+
+        self.apply_display_settings();
+        if self.swapchain_outdated {
+            self.recreate_swapchain()?;
+        }
+
+        Ok(())
     }
     
-    pub fn handle_event<T>(&mut self, event: &winit::event::Event<T>) {
-        self.camera().handle_event(event);
+    // Fails when the window can't be drawn to anymore.
+    pub fn handle_event<T>(&mut self, event: &winit::event::Event<T>) -> Result<(), AppError> {
+        // the camera only looks around while the cursor is grabbed
+        let look = matches!(event, Event::DeviceEvent { event: DeviceEvent::MouseMotion { .. }, .. });
+        if self.cursor_grabbed || !look {
+            self.camera().handle_event(event);
+        }
 
         if let Event::WindowEvent { event, .. } = event {
             self.egui_integration_handle_event(&event);
 
+            match event {
+                WindowEvent::KeyboardInput {
+                    input: KeyboardInput {
+                        virtual_keycode: Some(key),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                    ..
+                } if *key == self.settings.keys.grab_cursor => self.set_cursor_grabbed(!self.cursor_grabbed),
+                WindowEvent::KeyboardInput {
+                    input: KeyboardInput {
+                        virtual_keycode: Some(key),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                    ..
+                } if *key == self.settings.keys.record => self.recorder.toggle(self.clock.as_mut().unwrap()),
+                WindowEvent::KeyboardInput {
+                    input: KeyboardInput {
+                        virtual_keycode: Some(key),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                    ..
+                } if *key == self.settings.keys.screenshot => self.screenshot.request(),
+                WindowEvent::CursorMoved { position, .. } => {
+                    let scale_factor = self.window.as_ref().unwrap().scale_factor();
+                    let position = position.to_logical::<f32>(scale_factor);
+                    self.cursor = Some(egui::pos2(position.x, position.y));
+                }
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    button: MouseButton::Left,
+                    ..
+                } if !self.cursor_grabbed => self.pick(),
+                _ => (),
+            }
+
             if let WindowEvent::Resized(dims) = event {
                 self.update_surface_resolution(ash::vk::Extent2D {
                     width: dims.width,
                     height: dims.height,
                 });
-                self.recreate_swapchain();
+
+                // remember the size of the window, but not of the screen or a minimized window
+                let window = self.window.as_ref().unwrap();
+                if window.fullscreen().is_none() && dims.width > 0 && dims.height > 0 {
+                    let size = dims.to_logical::<u32>(window.scale_factor());
+                    self.settings.window.width = size.width;
+                    self.settings.window.height = size.height;
+                }
+
+                // resizing sends many events, the swapchain is recreated once before the next frame
+                self.swapchain_outdated = true;
             }
         }
 
         if let Event::RedrawEventsCleared = event {
-            self.render();
+            return self.render();
         }
+
+        Ok(())
     }
 
     pub fn camera(&mut self) -> &mut Camera {
-        self.camera.as_mut().unwrap()
+        self.viewports.as_mut().unwrap().active_mut()
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
     }
 
 }
 
+// Discrete GPUs are preferred, software rendering is the last resort.
+fn device_type_score(device_type: vk::PhysicalDeviceType) -> u32 {
+    match device_type {
+        vk::PhysicalDeviceType::DISCRETE_GPU => 4,
+        vk::PhysicalDeviceType::INTEGRATED_GPU => 3,
+        vk::PhysicalDeviceType::VIRTUAL_GPU => 2,
+        vk::PhysicalDeviceType::CPU => 1,
+        _ => 0,
+    }
+}
+
 impl Drop for App {
     fn drop(&mut self) {
+        // a failed start exits right away, the driver frees what was created so far
+        if !self.initialized {
+            return;
+        }
+
         self.cleanup_swapchain();
+        // a headless app draws without them
+        if self.inflight_fences.is_some() {
+            self.destroy_sync_objects();
+        }
 
         let device = self.device.as_ref().unwrap();
-        let surface_loader = self.surface_loader.as_ref().unwrap();
-        let debug_utils_loader = self.debug_utils_loader.as_ref().unwrap();
-        let mut egui_integration = self.egui_integration.take().unwrap();
         let allocator = self.allocator.take().unwrap();
 
         unsafe {
             device.device_wait_idle().unwrap();
             
-            egui_integration.destroy();
-            drop(egui_integration);
+            if let Some(mut egui_integration) = self.egui_integration.take() {
+                egui_integration.destroy();
+            }
             drop(allocator);
 
             drop(self.index_buffers.take().unwrap());
             drop(self.vertex_buffers.take().unwrap());
 
-            for descriptor_set_layout in self.descriptor_set_layouts.take().unwrap() {
-                device.destroy_descriptor_set_layout(descriptor_set_layout, None);
+            if self.recorder.is_recording() {
+                self.recorder.stop(self.clock.as_mut().unwrap());
             }
 
-            for render_finished_semaphore in self.render_finished_semaphores.take().unwrap() {
-                device.destroy_semaphore(render_finished_semaphore, None);
-            }
-
-            for image_available_semaphore in self.image_available_semaphores.take().unwrap() {
-                device.destroy_semaphore(image_available_semaphore, None);
-            }
-
-            for inflight_fence in self.inflight_fences.take().unwrap() {
-                device.destroy_fence(inflight_fence, None);
+            for descriptor_set_layout in self.descriptor_set_layouts.take().unwrap() {
+                device.destroy_descriptor_set_layout(descriptor_set_layout, None);
             }
 
             device.destroy_command_pool(self.command_pool.take().unwrap(), None);
 
             device.destroy_device(None);
 
-            surface_loader.destroy_surface(self.surface.take().unwrap(), None);
+            if let (Some(surface_loader), Some(surface)) = (self.surface_loader.as_ref(), self.surface.take()) {
+                surface_loader.destroy_surface(surface, None);
+            }
 
-            debug_utils_loader.destroy_debug_utils_messenger(self.debug_utils_messenger.take().unwrap(), None);
+            if let (Some(debug_utils_loader), Some(debug_utils_messenger)) =
+                (self.debug_utils_loader.as_ref(), self.debug_utils_messenger.take())
+            {
+                debug_utils_loader.destroy_debug_utils_messenger(debug_utils_messenger, None);
+            }
 
             self.instance.take().unwrap().destroy_instance(None);
 
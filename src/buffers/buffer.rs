@@ -127,6 +127,14 @@ impl Buffer {
     pub fn buffer(&self) -> &vk::Buffer {
         self.buffer.as_ref().unwrap()
     }
+
+    // The contents of a host visible buffer.
+    pub fn data(&self) -> &[u8] {
+        let allocation = self.allocation.as_ref().unwrap();
+        let buffer_size = *self.buffer_size.as_ref().unwrap() as usize;
+
+        &allocation.mapped_slice().unwrap()[..buffer_size]
+    }
 }
 
 impl Drop for Buffer {
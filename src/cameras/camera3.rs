@@ -1,6 +1,7 @@
 
+use crate::config::{Controls, KeyBindings};
+
 use std::{collections::HashMap, hash::Hash};
-use std::time::Instant;
 
 extern crate nalgebra as na;
 
@@ -44,9 +45,10 @@ pub struct Camera3 {
     view: na::Matrix4<f32>,
     proj: na::Matrix4<f32>,
 
-    time: Instant,
     input_map: HashMap<Input, ElementState>,
     actions: HashMap<Action, f32>,
+    // the action a key drives and in which direction
+    keys: HashMap<VirtualKeyCode, (Action, f32)>,
 }
 
 impl Camera3 {
@@ -64,8 +66,9 @@ impl Camera3 {
         let look_direction = (target - position).normalize();
         let right_direction = look_direction.cross(&na::Vector3::z()).normalize();
         
-        let movement_speed = 5.0;
-        let rotation_speed = 0.1;
+        let controls = Controls::default();
+        let movement_speed = controls.move_speed3;
+        let rotation_speed = controls.turn_speed;
 
         let flip_y = true;
 
@@ -82,8 +85,6 @@ impl Camera3 {
             zfar
         ).to_homogeneous();
 
-        let time = Instant::now();
-
         // println!("projective: {:?}", proj);
         let input_map = HashMap::new();
 
@@ -112,12 +113,29 @@ impl Camera3 {
             flip_y,
             view,
             proj,
-            time,
             input_map,
-            actions
+            actions,
+            keys: Self::key_map(&KeyBindings::default()),
         }
     }
 
+    fn key_map(keys: &KeyBindings) -> HashMap<VirtualKeyCode, (Action, f32)> {
+        HashMap::from([
+            (keys.forward, (Action::Forward, 1.0)),
+            (keys.back, (Action::Forward, -1.0)),
+            (keys.right, (Action::Right, 1.0)),
+            (keys.left, (Action::Right, -1.0)),
+            (keys.up, (Action::Up, 1.0)),
+            (keys.down, (Action::Up, -1.0)),
+        ])
+    }
+
+    pub fn set_controls(&mut self, controls: &Controls, keys: &KeyBindings) {
+        self.movement_speed = controls.move_speed3;
+        self.rotation_speed = controls.turn_speed;
+        self.keys = Self::key_map(keys);
+    }
+
 
     fn up_direction(&self) -> na::Vector3<f32> {
         self.right_direction.cross(&self.look_direction)
@@ -149,14 +167,8 @@ impl Camera3 {
                         }
                     };
 
-                    match key_code {
-                        VirtualKeyCode::W => *self.actions.get_mut(&Action::Forward).unwrap() += factor,
-                        VirtualKeyCode::S => *self.actions.get_mut(&Action::Forward).unwrap() -= factor,
-                        VirtualKeyCode::D => *self.actions.get_mut(&Action::Right).unwrap() += factor,
-                        VirtualKeyCode::A => *self.actions.get_mut(&Action::Right).unwrap() -= factor,
-                        VirtualKeyCode::C => *self.actions.get_mut(&Action::Up).unwrap() += factor,
-                        VirtualKeyCode::V => *self.actions.get_mut(&Action::Up).unwrap() -= factor,
-                        _ => ()
+                    if let Some(&(action, direction)) = self.keys.get(&key_code) {
+                        *self.actions.get_mut(&action).unwrap() += direction * factor;
                     }
                 }
             }
@@ -180,6 +192,48 @@ impl Camera3 {
         return self.look_direction
     }
 
+    pub fn set_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+        self.update_proj();
+    }
+
+    fn update_proj(&mut self) {
+        self.proj = na::Perspective3::new(
+            self.aspect,
+            self.fovy,
+            self.znear,
+            self.zfar
+        ).to_homogeneous();
+    }
+
+    pub fn projection_ui(&mut self, ui: &mut egui::Ui) {
+        let before = (self.fovy, self.znear, self.zfar);
+
+        ui.add(egui::Slider::new(&mut self.fovy, 0.1..=3.0).text("fovy"));
+        ui.add(egui::Slider::new(&mut self.znear, 0.01..=10.0).logarithmic(true).text("near"));
+        ui.add(egui::Slider::new(&mut self.zfar, 10.0..=10000.0).logarithmic(true).text("far"));
+
+        if (self.fovy, self.znear, self.zfar) != before {
+            self.update_proj();
+        }
+    }
+
+    // The transform of an eye moved by `offset` along the right direction,
+    // with the aspect ratio scaled by `aspect_scale`.
+    pub fn eye_transform(&self, offset: f32, aspect_scale: f32) -> na::Matrix4<f32> {
+        let proj = na::Perspective3::new(
+            self.aspect * aspect_scale,
+            self.fovy,
+            self.znear,
+            self.zfar
+        ).to_homogeneous();
+
+        // the right direction is the x axis of view space
+        let shift = na::Matrix4::new_translation(&na::Vector3::new(-offset, 0.0, 0.0));
+
+        proj * shift * self.view
+    }
+
 }
 
 impl super::Camera for Camera3 {
@@ -189,10 +243,7 @@ impl super::Camera for Camera3 {
         self.proj * self.view
     }
 
-    fn update_view(&mut self) {
-        let dt = self.time.elapsed().as_secs_f32();
-        self.time = Instant::now();
-
+    fn update_view(&mut self, dt: f32) {
         let mut flip_y = 1.0;
         if self.flip_y {
             flip_y = -1.0;
@@ -251,14 +302,8 @@ impl super::Camera for Camera3 {
                         }
                     };
 
-                    match key_code {
-                        VirtualKeyCode::W => *self.actions.get_mut(&Action::Forward).unwrap() += factor,
-                        VirtualKeyCode::S => *self.actions.get_mut(&Action::Forward).unwrap() -= factor,
-                        VirtualKeyCode::D => *self.actions.get_mut(&Action::Right).unwrap() += factor,
-                        VirtualKeyCode::A => *self.actions.get_mut(&Action::Right).unwrap() -= factor,
-                        VirtualKeyCode::C => *self.actions.get_mut(&Action::Up).unwrap() += factor,
-                        VirtualKeyCode::V => *self.actions.get_mut(&Action::Up).unwrap() -= factor,
-                        _ => ()
+                    if let Some(&(action, direction)) = self.keys.get(key_code) {
+                        *self.actions.get_mut(&action).unwrap() += direction * factor;
                     }
                 }
             }
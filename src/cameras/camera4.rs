@@ -1,9 +1,9 @@
 
 use crate::core::math::{self, cross4};
+use crate::config::{Controls, KeyBindings};
 use super::{Camera, Camera3};
 
 use std::{collections::HashMap, hash::Hash};
-use std::time::Instant;
 extern crate nalgebra as na;
 
 use winit::{
@@ -27,7 +27,35 @@ enum Action {
     Pitch,
 }
 
+// How the 4D camera maps its view onto the 3D space of `camera3`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Projection4 {
+    Perspective,
+    Orthographic,
+    // from the pole of the 3-sphere around the origin nearest to the camera,
+    // which maps glomes centered at the origin to the whole of 3D space
+    Stereographic,
+}
+
+impl Projection4 {
+    pub const ALL: [Projection4; 3] = [
+        Projection4::Perspective,
+        Projection4::Orthographic,
+        Projection4::Stereographic,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection4::Perspective => "perspective",
+            Projection4::Orthographic => "orthographic",
+            Projection4::Stereographic => "stereographic",
+        }
+    }
+}
+
+// Matches the uniform block of shader.vert, std140.
 #[derive(Copy, Debug, Clone)]
+#[repr(C)]
 #[allow(dead_code)]
 pub struct UniformBufferObject {
     pub cam4_trans: na::Matrix4<f32>,
@@ -35,14 +63,24 @@ pub struct UniformBufferObject {
     pub cam4_row: na::Vector4<f32>,
     pub cam3_trans: na::Matrix4<f32>,
     pub cam4_const: f32,
+    // the index of a `Projection4`
+    pub projection: i32,
+    pub sphere_radius: f32,
 }
 
+// Assertions that the fields are where shader.vert reads them, generated by build.rs.
+include!(concat!(env!("OUT_DIR"), "/uniform_layout.rs"));
+
 pub struct Camera4 {
     pub camera3: Camera3,
 
+    projection: Projection4,
     fovy: f32,
     near: f32,
     far: f32,
+    // half the width of the orthographic view volume
+    half_width: f32,
+    sphere_radius: f32,
 
     position: na::Point4<f32>,  // the position of the camera
     target: na::Point4<f32>,   // the point we look at
@@ -58,13 +96,23 @@ pub struct Camera4 {
     view: na::Matrix5<f32>,
     proj: na::Matrix5<f32>,
 
-    time: Instant,
     input_map: HashMap<Input, ElementState>,
     actions: HashMap<Action, f32>,
+    keys: HashMap<VirtualKeyCode, (Action, f32)>,
 }
 
 impl Camera4 {
     pub fn new() -> Self {
+        Self::looking_at(
+            na::Point4::new(0.0, 0.0, 0.0, 4.0),
+            na::Vector4::new(0.0, 1.0, 0.0, 0.0),
+            na::Vector4::new(0.0, 0.0, 1.0, 0.0),
+        )
+    }
+
+    // A camera at `position` looking at the origin, with `y` and `z` as the
+    // up and depth directions of the 3D space it projects onto.
+    pub fn looking_at(position: na::Point4<f32>, y: na::Vector4<f32>, z: na::Vector4<f32>) -> Self {
         let camera3 = Camera3::new();
 
         // let aspect = 1.0;
@@ -72,26 +120,21 @@ impl Camera4 {
         let near: f32 = -1.0;
         let far: f32 = -100.0;
         
-        let position = na::Point4::new(0.0, 0.0, 0.0, 4.0);
-        // let position = na::Point4::new(0.0, 0.0, 0.0, 4.0);
         let target = na::Point4::origin();
 
         let w = (target - position).normalize();
-        let y = na::Vector4::new(0.0, 1.0, 0.0, 0.0);
-        let z = na::Vector4::new(0.0, 0.0, 1.0, 0.0);
         let x = cross4(&y, &z, &w);
 
-        let movement_speed = 1.0;
-        let rotation_speed = 0.1;
+        let controls = Controls::default();
+        let movement_speed = controls.move_speed4;
+        let rotation_speed = controls.turn_speed;
 
-        let half_width = (near).abs() / 2.0 * (fovy / 2.0).tan();
-        let proj = math::ortho4_short(near, far, half_width) * math::perspective4(near, far);
+        let projection = Projection4::Perspective;
+        let proj = math::perspective4_short(near, far, fovy);
         // let proj = na::Matrix5::identity();
 
         let view = math::view4(&position, &x, &y, &z, &w);
 
-        let time = Instant::now();
-
         let input_map = HashMap::new();
 
         let actions = {
@@ -108,9 +151,12 @@ impl Camera4 {
 
         Self {
             camera3,
+            projection,
             fovy,
             near,
             far,
+            half_width: 2.0,
+            sphere_radius: 2.0,
             position,
             target,
             w,
@@ -121,12 +167,32 @@ impl Camera4 {
             rotation_speed,
             view,
             proj,
-            time,
             input_map,
-            actions
+            actions,
+            keys: Self::key_map(&KeyBindings::default()),
         }
     }
 
+    fn key_map(keys: &KeyBindings) -> HashMap<VirtualKeyCode, (Action, f32)> {
+        HashMap::from([
+            (keys.x_plus, (Action::X, 1.0)),
+            (keys.x_minus, (Action::X, -1.0)),
+            (keys.y_plus, (Action::Y, 1.0)),
+            (keys.y_minus, (Action::Y, -1.0)),
+            (keys.z_plus, (Action::Z, 1.0)),
+            (keys.z_minus, (Action::Z, -1.0)),
+            (keys.w_plus, (Action::W, 1.0)),
+            (keys.w_minus, (Action::W, -1.0)),
+        ])
+    }
+
+    pub fn set_controls(&mut self, controls: &Controls, keys: &KeyBindings) {
+        self.movement_speed = controls.move_speed4;
+        self.rotation_speed = controls.turn_speed;
+        self.keys = Self::key_map(keys);
+        self.camera3.set_controls(controls, keys);
+    }
+
     pub fn data(&self, model: &na::Matrix5<f32>) -> UniformBufferObject {
         let transform = self.transform() * model;
         let cam4_col = transform.fixed_slice::<4,1>(0,4);
@@ -140,6 +206,82 @@ impl Camera4 {
             cam4_row: cam4_row.into(),
             cam3_trans,
             cam4_const,
+            projection: self.projection as i32,
+            sphere_radius: self.sphere_radius,
+        }
+    }
+
+    // Project a model space point into the 3D space of `camera3` the same way the vertex shader does.
+    pub fn project4(&self, model: &na::Matrix5<f32>, pos: &[f32; 4]) -> na::Point3<f32> {
+        let p = self.transform() * model * na::Vector5::new(pos[0], pos[1], pos[2], pos[3], 1.0);
+        let pos4d = p.fixed_rows::<4>(0) / p[4];
+        let pos3d = match self.projection {
+            Projection4::Perspective => pos4d.xyz() / pos4d[3],
+            Projection4::Orthographic => pos4d.xyz(),
+            Projection4::Stereographic => {
+                let r = self.sphere_radius;
+                pos4d.xyz() * r / (r - pos4d[3])
+            }
+        };
+
+        na::Point3::from(pos3d)
+    }
+
+    // Project a model space point the same way the vertex shader does.
+    // Returns normalized device coordinates, or `None` for points behind the 3D camera.
+    pub fn project(&self, model: &na::Matrix5<f32>, pos: &[f32; 4]) -> Option<na::Point3<f32>> {
+        let clip = self.camera3.transform() * self.project4(model, pos).to_homogeneous();
+        if clip[3] <= 0.0 {
+            return None;
+        }
+
+        Some(na::Point3::new(clip[0] / clip[3], clip[1] / clip[3], clip[2] / clip[3]))
+    }
+
+    fn update_proj(&mut self) {
+        self.proj = match self.projection {
+            Projection4::Perspective => math::perspective4_short(self.near, self.far, self.fovy),
+            Projection4::Orthographic => math::ortho4_short(self.near, self.far, self.half_width),
+            // only the orientation of the view is used
+            Projection4::Stereographic => na::Matrix5::identity(),
+        };
+    }
+
+    pub fn projection_ui(&mut self, ui: &mut egui::Ui) {
+        let before = (self.projection, self.fovy, self.near, self.far, self.half_width);
+
+        egui::ComboBox::from_id_source("projection4")
+            .selected_text(self.projection.name())
+            .show_ui(ui, |ui| {
+                for projection in Projection4::ALL {
+                    ui.selectable_value(&mut self.projection, projection, projection.name());
+                }
+            });
+
+        match self.projection {
+            Projection4::Perspective => {
+                ui.add(egui::Slider::new(&mut self.fovy, 0.1..=3.0).text("fovy"));
+            }
+            Projection4::Orthographic => {
+                ui.add(egui::Slider::new(&mut self.half_width, 0.1..=10.0).text("half width"));
+            }
+            Projection4::Stereographic => {
+                ui.add(egui::Slider::new(&mut self.sphere_radius, 0.1..=10.0).text("sphere radius"));
+            }
+        }
+
+        // near and far lie on the negative w axis of the view, shown as distances
+        if self.projection != Projection4::Stereographic {
+            let mut near = -self.near;
+            let mut far = -self.far;
+            ui.add(egui::Slider::new(&mut near, 0.01..=10.0).logarithmic(true).text("near"));
+            ui.add(egui::Slider::new(&mut far, 1.0..=1000.0).logarithmic(true).text("far"));
+            self.near = -near;
+            self.far = -far.max(near + 0.01);
+        }
+
+        if (self.projection, self.fovy, self.near, self.far, self.half_width) != before {
+            self.update_proj();
         }
     }
 
@@ -172,15 +314,17 @@ impl Camera for Camera4 {
     type Transform = na::Matrix5<f32>;
 
     fn transform(&self) -> Self::Transform {
-        self.proj * self.view
+        match self.projection {
+            Projection4::Stereographic => {
+                math::view4(&na::Point4::origin(), &self.x, &self.y, &self.z, &self.w)
+            }
+            _ => self.proj * self.view,
+        }
         // na::Matrix5::<f32>::identity()
     }
 
-    fn update_view(&mut self) {
-        self.camera3.update_view();
-
-        let dt = self.time.elapsed().as_secs_f32();
-        self.time = Instant::now();
+    fn update_view(&mut self, dt: f32) {
+        self.camera3.update_view(dt);
 
         // println!("{:?}", self.actions);
         let move_direction = 
@@ -231,16 +375,8 @@ impl Camera for Camera4 {
                         }
                     };
 
-                    match key_code {
-                        VirtualKeyCode::Y => *self.actions.get_mut(&Action::X).unwrap() += factor,
-                        VirtualKeyCode::H => *self.actions.get_mut(&Action::X).unwrap() -= factor,
-                        VirtualKeyCode::U => *self.actions.get_mut(&Action::Y).unwrap() += factor,
-                        VirtualKeyCode::J => *self.actions.get_mut(&Action::Y).unwrap() -= factor,
-                        VirtualKeyCode::I => *self.actions.get_mut(&Action::Z).unwrap() += factor,
-                        VirtualKeyCode::K => *self.actions.get_mut(&Action::Z).unwrap() -= factor,
-                        VirtualKeyCode::O => *self.actions.get_mut(&Action::W).unwrap() += factor,
-                        VirtualKeyCode::L => *self.actions.get_mut(&Action::W).unwrap() -= factor,
-                        _ => ()
+                    if let Some(&(action, direction)) = self.keys.get(key_code) {
+                        *self.actions.get_mut(&action).unwrap() += direction * factor;
                     }
                 }
             }
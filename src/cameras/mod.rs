@@ -6,11 +6,17 @@ mod camera4;
 pub use camera4::Camera4;
 pub use camera4::UniformBufferObject;
 
+mod stereo;
+pub use stereo::{Eye, Stereo};
+
+mod viewports;
+pub use viewports::Viewports;
+
 extern crate nalgebra as na;
 
 pub trait Camera {
     type Transform;
     fn transform(&self) -> Self::Transform;
-    fn update_view(&mut self);
+    fn update_view(&mut self, dt: f32);
     fn handle_event<T>(&mut self, event: &winit::event::Event<T>);
 }
\ No newline at end of file
@@ -0,0 +1,79 @@
+use crate::pipelines::ColorMask;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StereoMode {
+    Off,
+    SideBySide,
+    TopBottom,
+    Anaglyph,
+}
+
+// One rendering of the scene: where it goes on the target, in fractions of its size,
+// and how the 3D camera is shifted and reshaped for it.
+#[derive(Debug, Copy, Clone)]
+pub struct Eye {
+    // x, y, width and height
+    pub area: [f32; 4],
+    // along the right direction of the 3D camera
+    pub offset: f32,
+    // of the eye's aspect ratio relative to the whole target
+    pub aspect_scale: f32,
+    pub mask: ColorMask,
+}
+
+pub struct Stereo {
+    pub mode: StereoMode,
+    pub eye_separation: f32,
+    // cross-eyed viewing of side-by-side images
+    pub swap_eyes: bool,
+}
+
+impl Default for Stereo {
+    fn default() -> Self {
+        Self {
+            mode: StereoMode::Off,
+            eye_separation: 0.065,
+            swap_eyes: false,
+        }
+    }
+}
+
+impl Stereo {
+    // The left eye comes first.
+    pub fn eyes(&self) -> Vec<Eye> {
+        let half = self.eye_separation / 2.0;
+        let (left, right) = if self.swap_eyes { (half, -half) } else { (-half, half) };
+
+        let eye = |area, offset, aspect_scale, mask| Eye { area, offset, aspect_scale, mask };
+
+        match self.mode {
+            StereoMode::Off => vec![eye([0.0, 0.0, 1.0, 1.0], 0.0, 1.0, ColorMask::All)],
+            StereoMode::SideBySide => vec![
+                eye([0.0, 0.0, 0.5, 1.0], left, 0.5, ColorMask::All),
+                eye([0.5, 0.0, 0.5, 1.0], right, 0.5, ColorMask::All),
+            ],
+            StereoMode::TopBottom => vec![
+                eye([0.0, 0.0, 1.0, 0.5], left, 2.0, ColorMask::All),
+                eye([0.0, 0.5, 1.0, 0.5], right, 2.0, ColorMask::All),
+            ],
+            StereoMode::Anaglyph => vec![
+                eye([0.0, 0.0, 1.0, 1.0], left, 1.0, ColorMask::Red),
+                eye([0.0, 0.0, 1.0, 1.0], right, 1.0, ColorMask::Cyan),
+            ],
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, StereoMode::Off, "off");
+            ui.radio_value(&mut self.mode, StereoMode::SideBySide, "side by side");
+            ui.radio_value(&mut self.mode, StereoMode::TopBottom, "top bottom");
+            ui.radio_value(&mut self.mode, StereoMode::Anaglyph, "anaglyph");
+        });
+
+        ui.add_enabled_ui(self.mode != StereoMode::Off, |ui| {
+            ui.add(egui::Slider::new(&mut self.eye_separation, 0.0..=0.5).text("eye separation"));
+            ui.checkbox(&mut self.swap_eyes, "swap eyes");
+        });
+    }
+}
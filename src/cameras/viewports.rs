@@ -0,0 +1,220 @@
+use super::{Camera, Camera4, Eye, Stereo};
+use crate::config::{Controls, KeyBindings};
+
+extern crate nalgebra as na;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Layout {
+    Single,
+    Split,
+    Quad,
+}
+
+// The 3D space a viewport's 4D camera projects onto.
+// The camera starts on the remaining axis, looking at the origin.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Onto {
+    Xyz,
+    Xyw,
+    Xzw,
+    Yzw,
+}
+
+impl Onto {
+    const ALL: [Onto; 4] = [Onto::Xyz, Onto::Xyw, Onto::Xzw, Onto::Yzw];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Onto::Xyz => "xyz",
+            Onto::Xyw => "xyw",
+            Onto::Xzw => "xzw",
+            Onto::Yzw => "yzw",
+        }
+    }
+
+    fn camera(&self) -> Camera4 {
+        let axis = |i| na::Vector4::ith(i, 1.0);
+        let (eye, y, z) = match self {
+            Onto::Xyz => return Camera4::new(),
+            Onto::Xyw => (2, 1, 3),
+            Onto::Xzw => (1, 2, 3),
+            Onto::Yzw => (0, 1, 2),
+        };
+
+        Camera4::looking_at(na::Point4::from(axis(eye) * 4.0), axis(y), axis(z))
+    }
+}
+
+pub struct Viewport {
+    pub onto: Onto,
+    pub camera: Camera4,
+}
+
+// Splits the window into viewports, each with its own camera.
+// Only the active viewport takes camera input and shows the overlays.
+pub struct Viewports {
+    pub layout: Layout,
+    viewports: Vec<Viewport>,
+    active: usize,
+    // given to every camera, also the ones made later
+    controls: Controls,
+    keys: KeyBindings,
+}
+
+impl Viewports {
+    pub fn new() -> Self {
+        let viewports = Onto::ALL
+            .iter()
+            .map(|onto| Viewport { onto: *onto, camera: onto.camera() })
+            .collect();
+
+        Self {
+            layout: Layout::Single,
+            viewports,
+            active: 0,
+            controls: Controls::default(),
+            keys: KeyBindings::default(),
+        }
+    }
+
+    pub fn controls(&self) -> &Controls {
+        &self.controls
+    }
+
+    pub fn set_controls(&mut self, controls: &Controls, keys: &KeyBindings) {
+        self.controls = controls.clone();
+        self.keys = keys.clone();
+
+        for viewport in self.viewports.iter_mut() {
+            viewport.camera.set_controls(controls, keys);
+        }
+    }
+
+    // x, y, width and height of the shown viewports, in fractions of the window.
+    pub fn areas(&self) -> Vec<[f32; 4]> {
+        match self.layout {
+            Layout::Single => vec![[0.0, 0.0, 1.0, 1.0]],
+            Layout::Split => vec![[0.0, 0.0, 0.5, 1.0], [0.5, 0.0, 0.5, 1.0]],
+            Layout::Quad => vec![
+                [0.0, 0.0, 0.5, 0.5],
+                [0.5, 0.0, 0.5, 0.5],
+                [0.0, 0.5, 0.5, 0.5],
+                [0.5, 0.5, 0.5, 0.5],
+            ],
+        }
+    }
+
+    // Every rendering of the scene this frame with the index of its camera,
+    // each eye placed inside the area of its viewport.
+    pub fn views(&self, stereo: &Stereo) -> Vec<(usize, Eye)> {
+        let eyes = stereo.eyes();
+
+        self.areas()
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, [x, y, width, height])| {
+                eyes.iter().map(move |eye| {
+                    let [ex, ey, ewidth, eheight] = eye.area;
+                    let area = [x + ex * width, y + ey * height, ewidth * width, eheight * height];
+
+                    (i, Eye { area, ..*eye })
+                })
+            })
+            .collect()
+    }
+
+    pub fn camera(&self, index: usize) -> &Camera4 {
+        &self.viewports[index].camera
+    }
+
+    pub fn active(&self) -> &Camera4 {
+        &self.viewports[self.active].camera
+    }
+
+    pub fn active_mut(&mut self) -> &mut Camera4 {
+        &mut self.viewports[self.active].camera
+    }
+
+    // The rectangle of the active viewport inside `screen`.
+    pub fn active_rect(&self, screen: egui::Rect) -> egui::Rect {
+        let [x, y, width, height] = self.areas()[self.active];
+
+        egui::Rect::from_min_size(
+            screen.min + egui::vec2(x * screen.width(), y * screen.height()),
+            egui::vec2(width * screen.width(), height * screen.height()),
+        )
+    }
+
+    // Make the viewport under `pos` active.
+    pub fn activate_at(&mut self, pos: egui::Pos2, screen: egui::Rect) {
+        let x = (pos.x - screen.left()) / screen.width();
+        let y = (pos.y - screen.top()) / screen.height();
+
+        if let Some(index) = self.areas().iter().position(|[left, top, width, height]| {
+            (*left..left + width).contains(&x) && (*top..top + height).contains(&y)
+        }) {
+            self.active = index;
+        }
+    }
+
+    // Give every camera the aspect ratio of its viewport on a window with `aspect`.
+    pub fn update_view(&mut self, dt: f32, aspect: f32) {
+        let areas = self.areas();
+
+        for (i, viewport) in self.viewports.iter_mut().enumerate() {
+            let [_, _, width, height] = areas.get(i).copied().unwrap_or([0.0, 0.0, 1.0, 1.0]);
+
+            // a minimized window has no aspect ratio
+            if aspect.is_normal() {
+                viewport.camera.camera3.set_aspect(aspect * width / height);
+            }
+            viewport.camera.update_view(dt);
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.layout, Layout::Single, "single");
+            ui.radio_value(&mut self.layout, Layout::Split, "two");
+            ui.radio_value(&mut self.layout, Layout::Quad, "four");
+        });
+        self.active = self.active.min(self.areas().len() - 1);
+
+        for i in 0..self.areas().len() {
+            let viewport = &mut self.viewports[i];
+            let previous = viewport.onto;
+
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.active, i, format!("viewport {}", i + 1));
+
+                egui::ComboBox::from_id_source(("viewport_onto", i))
+                    .selected_text(format!("onto {}", viewport.onto.name()))
+                    .show_ui(ui, |ui| {
+                        for onto in Onto::ALL {
+                            ui.selectable_value(&mut viewport.onto, onto, onto.name());
+                        }
+                    });
+
+                if ui.button("Reset").clicked() || viewport.onto != previous {
+                    viewport.camera = viewport.onto.camera();
+                    viewport.camera.set_controls(&self.controls, &self.keys);
+                }
+            });
+        }
+    }
+
+    // Outline the active viewport when there is more than one.
+    pub fn paint(&self, ctx: &egui::Context) {
+        if self.layout == Layout::Single {
+            return;
+        }
+
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("viewports"),
+        ));
+        let rect = self.active_rect(ctx.input().screen_rect());
+
+        painter.rect_stroke(rect.shrink(1.0), 0.0, egui::Stroke::new(2.0, egui::Color32::GRAY));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::buffers::Buffer;
+
+use ash::vk;
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+// Record a copy of `image`, in `layout`, into the host visible `buffer`.
+// The image is back in `layout` afterwards.
+pub fn record_readback(
+    device: &ash::Device,
+    command_buffer: vk::CommandBuffer,
+    image: vk::Image,
+    layout: vk::ImageLayout,
+    extent: vk::Extent2D,
+    buffer: &Buffer,
+) {
+    let subresource_range = vk::ImageSubresourceRange {
+        aspect_mask: vk::ImageAspectFlags::COLOR,
+        base_mip_level: 0,
+        level_count: 1,
+        base_array_layer: 0,
+        layer_count: 1,
+    };
+
+    let to_transfer = vk::ImageMemoryBarrier::builder()
+        .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+        .dst_access_mask(vk::AccessFlags::TRANSFER_READ)
+        .old_layout(layout)
+        .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+        .image(image)
+        .subresource_range(subresource_range);
+
+    let to_attachment = vk::ImageMemoryBarrier::builder()
+        .src_access_mask(vk::AccessFlags::TRANSFER_READ)
+        .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+        .old_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+        .new_layout(layout)
+        .image(image)
+        .subresource_range(subresource_range);
+
+    let to_host = vk::BufferMemoryBarrier::builder()
+        .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+        .dst_access_mask(vk::AccessFlags::HOST_READ)
+        .buffer(*buffer.buffer())
+        .size(vk::WHOLE_SIZE);
+
+    let region = vk::BufferImageCopy::builder()
+        .image_subresource(vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        })
+        .image_extent(vk::Extent3D {
+            width: extent.width,
+            height: extent.height,
+            depth: 1,
+        });
+
+    unsafe {
+        device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            &[to_transfer.build()],
+        );
+
+        device.cmd_copy_image_to_buffer(
+            command_buffer,
+            image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            *buffer.buffer(),
+            &[region.build()],
+        );
+
+        device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT | vk::PipelineStageFlags::HOST,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[to_host.build()],
+            &[to_attachment.build()],
+        );
+    }
+}
+
+// Tightly packed RGB pixels from a readback of an 8 bit per channel image.
+// Returns `None` for other formats.
+pub fn to_rgb(data: &[u8], format: vk::Format) -> Option<Vec<u8>> {
+    let bgr = match format {
+        vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB => true,
+        vk::Format::R8G8B8A8_UNORM | vk::Format::R8G8B8A8_SRGB => false,
+        _ => return None,
+    };
+
+    Some(
+        data.chunks_exact(4)
+            .flat_map(|p| if bgr { [p[2], p[1], p[0]] } else { [p[0], p[1], p[2]] })
+            .collect()
+    )
+}
+
+pub fn write_png(path: &Path, width: u32, height: u32, rgb: &[u8]) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgb))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Write a readback of an image with the given extent and format to a PNG file.
+pub fn save_readback(path: &Path, data: &[u8], extent: vk::Extent2D, format: vk::Format) -> Result<(), String> {
+    let rgb = to_rgb(data, format).ok_or_else(|| format!("Can't capture the surface format {:?}.", format))?;
+
+    write_png(path, extent.width, extent.height, &rgb)
+}
@@ -0,0 +1,11 @@
+mod image;
+pub use image::{record_readback, save_readback};
+
+mod recorder;
+pub use recorder::Recorder;
+
+mod offscreen;
+pub use offscreen::Offscreen;
+
+mod screenshot;
+pub use screenshot::Screenshot;
@@ -0,0 +1,170 @@
+use ash::vk;
+use gpu_allocator::vulkan::*;
+use gpu_allocator::MemoryLocation;
+
+use std::sync::{Arc, Mutex};
+
+struct Attachment {
+    image: vk::Image,
+    view: vk::ImageView,
+    allocation: Option<Allocation>,
+}
+
+// Color and depth images with a framebuffer for the scene render pass,
+// at an extent independent of the swapchain.
+pub struct Offscreen {
+    device: ash::Device,
+    allocator: Arc<Mutex<Allocator>>,
+    extent: vk::Extent2D,
+    color: Attachment,
+    depth: Attachment,
+    framebuffer: vk::Framebuffer,
+}
+
+impl Offscreen {
+    pub fn new(
+        device: ash::Device,
+        allocator: Arc<Mutex<Allocator>>,
+        render_pass: vk::RenderPass,
+        format: vk::Format,
+        extent: vk::Extent2D,
+    ) -> Self {
+        let color = create_attachment(
+            &device,
+            &allocator,
+            format,
+            extent,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC,
+            vk::ImageAspectFlags::COLOR,
+        );
+        let depth = create_attachment(
+            &device,
+            &allocator,
+            vk::Format::D32_SFLOAT,
+            extent,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+            vk::ImageAspectFlags::DEPTH,
+        );
+
+        let attachments = [color.view, depth.view];
+        let framebuffer_create_info = vk::FramebufferCreateInfo::builder()
+            .render_pass(render_pass)
+            .attachments(&attachments)
+            .width(extent.width)
+            .height(extent.height)
+            .layers(1);
+
+        let framebuffer = unsafe {
+            device
+                .create_framebuffer(&framebuffer_create_info, None)
+                .unwrap()
+        };
+
+        Self {
+            device,
+            allocator,
+            extent,
+            color,
+            depth,
+            framebuffer,
+        }
+    }
+
+    pub fn extent(&self) -> vk::Extent2D {
+        self.extent
+    }
+
+    pub fn image(&self) -> vk::Image {
+        self.color.image
+    }
+
+    pub fn framebuffer(&self) -> vk::Framebuffer {
+        self.framebuffer
+    }
+}
+
+impl Drop for Offscreen {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_framebuffer(self.framebuffer, None);
+
+            for attachment in [&mut self.color, &mut self.depth] {
+                self.device.destroy_image_view(attachment.view, None);
+                self.device.destroy_image(attachment.image, None);
+                self.allocator
+                    .lock().unwrap()
+                    .free(attachment.allocation.take().unwrap())
+                    .unwrap();
+            }
+        }
+    }
+}
+
+fn create_attachment(
+    device: &ash::Device,
+    allocator: &Arc<Mutex<Allocator>>,
+    format: vk::Format,
+    extent: vk::Extent2D,
+    usage: vk::ImageUsageFlags,
+    aspect_mask: vk::ImageAspectFlags,
+) -> Attachment {
+    let image_create_info = vk::ImageCreateInfo::builder()
+        .image_type(vk::ImageType::TYPE_2D)
+        .format(format)
+        .extent(vk::Extent3D {
+            width: extent.width,
+            height: extent.height,
+            depth: 1,
+        })
+        .mip_levels(1)
+        .array_layers(1)
+        .samples(vk::SampleCountFlags::TYPE_1)
+        .tiling(vk::ImageTiling::OPTIMAL)
+        .usage(usage)
+        .sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+    let image = unsafe {
+        device.create_image(&image_create_info, None).unwrap()
+    };
+
+    let requirements = unsafe {
+        device.get_image_memory_requirements(image)
+    };
+
+    let allocation = allocator
+        .lock().unwrap()
+        .allocate(&AllocationCreateDesc {
+            name: "offscreen attachment",
+            requirements,
+            location: MemoryLocation::GpuOnly,
+            linear: false,
+        }).unwrap();
+
+    unsafe {
+        device
+            .bind_image_memory(image, allocation.memory(), allocation.offset())
+            .unwrap();
+    }
+
+    let view_create_info = vk::ImageViewCreateInfo::builder()
+        .subresource_range(
+            vk::ImageSubresourceRange::builder()
+                .aspect_mask(aspect_mask)
+                .level_count(1)
+                .layer_count(1)
+                .build(),
+        )
+        .image(image)
+        .format(format)
+        .view_type(vk::ImageViewType::TYPE_2D);
+
+    let view = unsafe {
+        device.create_image_view(&view_create_info, None).unwrap()
+    };
+
+    Attachment {
+        image,
+        view,
+        allocation: Some(allocation),
+    }
+}
@@ -0,0 +1,231 @@
+use super::image::{record_readback, to_rgb, write_png};
+use crate::buffers::Buffer;
+use crate::utils::Clock;
+
+use ash::vk;
+use gpu_allocator::vulkan::Allocator;
+use gpu_allocator::MemoryLocation;
+
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+struct Frame {
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    rgb: Vec<u8>,
+}
+
+// Writes the rendered view, without the user interface, to numbered PNG files.
+//
+// While recording the clock runs with a fixed step of 1 / `fps` seconds,
+// so a sequence played back at `fps / every` frames per second has the exact speed.
+pub struct Recorder {
+    pub fps: f32,
+    // capture every Nth simulation frame
+    pub every: u32,
+
+    directory: Option<PathBuf>,
+    frames_written: u32,
+    last_clock_frame: Option<u64>,
+    previous_fixed_step: Option<f32>,
+
+    buffer: Option<Buffer>,
+    extent: vk::Extent2D,
+    pending: Option<PathBuf>,
+
+    sender: Option<Sender<Frame>>,
+    worker: Option<JoinHandle<()>>,
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self {
+            fps: 60.0,
+            every: 1,
+            directory: None,
+            frames_written: 0,
+            last_clock_frame: None,
+            previous_fixed_step: None,
+            buffer: None,
+            extent: vk::Extent2D::default(),
+            pending: None,
+            sender: None,
+            worker: None,
+            error: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl Recorder {
+    pub fn is_recording(&self) -> bool {
+        self.directory.is_some()
+    }
+
+    pub fn toggle(&mut self, clock: &mut Clock) {
+        if self.is_recording() {
+            self.stop(clock);
+        } else {
+            self.start(clock);
+        }
+    }
+
+    // Start a new sequence in the first unused `captures/capture_N` directory.
+    pub fn start(&mut self, clock: &mut Clock) {
+        let directory = (0..)
+            .map(|i| PathBuf::from("captures").join(format!("capture_{}", i)))
+            .find(|path| !path.exists())
+            .unwrap();
+
+        if let Err(e) = std::fs::create_dir_all(&directory) {
+            *self.error.lock().unwrap() = Some(format!("{}: {}", directory.display(), e));
+            return;
+        }
+
+        let (sender, receiver) = channel::<Frame>();
+        let error = Arc::clone(&self.error);
+        *error.lock().unwrap() = None;
+
+        // PNG encoding is slow, so it runs off the render thread
+        self.worker = Some(std::thread::spawn(move || {
+            for frame in receiver {
+                if let Err(e) = write_png(&frame.path, frame.width, frame.height, &frame.rgb) {
+                    error.lock().unwrap().get_or_insert(e);
+                }
+            }
+        }));
+        self.sender = Some(sender);
+
+        self.previous_fixed_step = clock.fixed_step;
+        clock.fixed_step = Some(1.0 / self.fps);
+
+        self.directory = Some(directory);
+        self.frames_written = 0;
+        self.last_clock_frame = None;
+    }
+
+    // Stop recording, after the queued frames are written.
+    pub fn stop(&mut self, clock: &mut Clock) {
+        self.directory = None;
+        self.pending = None;
+        self.buffer = None;
+        clock.fixed_step = self.previous_fixed_step.take();
+
+        drop(self.sender.take());
+        if let Some(worker) = self.worker.take() {
+            worker.join().unwrap();
+        }
+    }
+
+    // Record a copy of the rendered swapchain image if this frame is captured.
+    pub fn record(
+        &mut self,
+        device: &ash::Device,
+        allocator: &Arc<Mutex<Allocator>>,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        extent: vk::Extent2D,
+        clock: &Clock,
+    ) {
+        let directory = match self.directory.as_ref() {
+            Some(directory) => directory,
+            None => return,
+        };
+
+        // a paused clock doesn't produce new frames
+        let frame = clock.frame();
+        if self.last_clock_frame == Some(frame) {
+            return;
+        }
+        self.last_clock_frame = Some(frame);
+
+        if !frame.is_multiple_of(self.every.max(1) as u64) {
+            return;
+        }
+
+        if self.buffer.is_none() || self.extent != extent {
+            self.extent = extent;
+            self.buffer = Some(Buffer::new(
+                device.clone(),
+                extent.width as u64 * extent.height as u64 * 4,
+                vk::BufferUsageFlags::TRANSFER_DST,
+                Arc::clone(allocator),
+                MemoryLocation::GpuToCpu,
+            ));
+        }
+
+        record_readback(
+            device,
+            command_buffer,
+            image,
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            extent,
+            self.buffer.as_ref().unwrap(),
+        );
+
+        self.pending = Some(directory.join(format!("frame_{:06}.png", self.frames_written)));
+        self.frames_written += 1;
+    }
+
+    // Hand the frame copied by `record` to the writer, once `fence` says the copy is done.
+    pub fn finish(&mut self, device: &ash::Device, fence: vk::Fence, format: vk::Format) {
+        let path = match self.pending.take() {
+            Some(path) => path,
+            None => return,
+        };
+
+        unsafe {
+            device
+                .wait_for_fences(&[fence], true, u64::MAX)
+                .expect("Wait for fence failed.");
+        }
+
+        let rgb = match to_rgb(self.buffer.as_ref().unwrap().data(), format) {
+            Some(rgb) => rgb,
+            None => {
+                *self.error.lock().unwrap() = Some(format!("Can't capture the surface format {:?}.", format));
+                return;
+            }
+        };
+
+        if let Some(sender) = self.sender.as_ref() {
+            sender
+                .send(Frame {
+                    path,
+                    width: self.extent.width,
+                    height: self.extent.height,
+                    rgb,
+                })
+                .ok();
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, clock: &mut Clock, supported: bool) {
+        if !supported {
+            ui.label("The surface doesn't support capturing.");
+            return;
+        }
+
+        ui.add_enabled_ui(!self.is_recording(), |ui| {
+            ui.add(egui::Slider::new(&mut self.fps, 1.0..=240.0).text("simulation fps"));
+            ui.add(egui::Slider::new(&mut self.every, 1..=10).text("every nth frame"));
+        });
+        ui.label(format!("plays back at {:.1} fps", self.fps / self.every.max(1) as f32));
+
+        let text = if self.is_recording() { "Stop (F9)" } else { "Record (F9)" };
+        if ui.button(text).clicked() {
+            self.toggle(clock);
+        }
+
+        if let Some(directory) = self.directory.as_ref() {
+            ui.label(format!("{} frames to {}", self.frames_written, directory.display()));
+        }
+
+        if let Some(error) = self.error.lock().unwrap().as_ref() {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+}
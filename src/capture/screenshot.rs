@@ -0,0 +1,76 @@
+use super::image::save_readback;
+
+use ash::vk;
+
+use std::path::PathBuf;
+
+// A single still of the scene, saved to `screenshots/screenshot_N.png`.
+//
+// Without the overlay the scene is rendered offscreen at `scale` times the window size.
+// The user interface only exists at window resolution, so with the overlay
+// the presented swapchain image is saved instead.
+pub struct Screenshot {
+    pub scale: f32,
+    pub overlay: bool,
+    requested: bool,
+    message: Option<Result<String, String>>,
+}
+
+impl Default for Screenshot {
+    fn default() -> Self {
+        Self {
+            scale: 4.0,
+            overlay: false,
+            requested: false,
+            message: None,
+        }
+    }
+}
+
+impl Screenshot {
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    pub fn take_request(&mut self) -> bool {
+        std::mem::take(&mut self.requested)
+    }
+
+    // Save a readback of an image with the given extent and format.
+    pub fn save(&mut self, data: &[u8], extent: vk::Extent2D, format: vk::Format) {
+        let path = (0..)
+            .map(|i| PathBuf::from("screenshots").join(format!("screenshot_{}.png", i)))
+            .find(|path| !path.exists())
+            .unwrap();
+
+        let result = std::fs::create_dir_all("screenshots")
+            .map_err(|e| format!("screenshots: {}", e))
+            .and_then(|_| save_readback(&path, data, extent, format));
+
+        self.message = Some(result.map(|_| {
+            format!("Saved {} ({}x{})", path.display(), extent.width, extent.height)
+        }));
+    }
+
+    pub fn fail(&mut self, error: String) {
+        self.message = Some(Err(error));
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, supported: bool) {
+        ui.checkbox(&mut self.overlay, "include the user interface");
+        ui.add_enabled(
+            !self.overlay,
+            egui::Slider::new(&mut self.scale, 0.25..=8.0).text("times the window size"),
+        );
+
+        if ui.add_enabled(supported || !self.overlay, egui::Button::new("Screenshot (F12)")).clicked() {
+            self.request();
+        }
+
+        match self.message.as_ref() {
+            Some(Ok(message)) => { ui.label(message); }
+            Some(Err(error)) => { ui.colored_label(egui::Color32::RED, error); }
+            None => (),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::config::RenderMode;
+
+use clap::builder::FalseyValueParser;
+use clap::{Parser, Subcommand, ValueEnum};
+
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "A viewer for four dimensional polytopes")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    // Needs the Vulkan SDK or the vulkan-validation-layers package.
+    // Messages are logged with the target `vulkan`, e.g. RUST_LOG=vulkan=info.
+    /// Enable the Vulkan validation layer
+    #[arg(long, global = true, env = "HYPER_OCULUS_VALIDATION", value_parser = FalseyValueParser::new())]
+    pub validation: bool,
+
+    /// The GPU to use: its index, part of its name, or discrete, integrated, virtual or cpu
+    #[arg(long, global = true, env = "HYPER_OCULUS_DEVICE")]
+    pub device: Option<String>,
+
+    // For working on src/shaders in a source checkout.
+    /// Recompile the shaders and rebuild the pipeline when they change
+    #[arg(long, global = true, env = "HYPER_OCULUS_WATCH_SHADERS", value_parser = FalseyValueParser::new())]
+    pub watch_shaders: bool,
+
+    // The flags below override config.toml for this run, and are not saved to it.
+    /// The window width in logical pixels, or the image width in pixels
+    #[arg(long, global = true)]
+    pub width: Option<u32>,
+
+    /// The window height in logical pixels, or the image height in pixels
+    #[arg(long, global = true)]
+    pub height: Option<u32>,
+
+    /// Start fullscreen
+    #[arg(long, global = true, overrides_with = "windowed")]
+    pub fullscreen: bool,
+
+    /// Start in a window
+    #[arg(long, global = true, overrides_with = "fullscreen")]
+    pub windowed: bool,
+
+    /// Wait for the vertical blank (the fifo present mode)
+    #[arg(long, global = true, overrides_with = "no_vsync")]
+    pub vsync: bool,
+
+    /// Present as soon as a frame is ready, which may tear (the immediate present mode)
+    #[arg(long, global = true, overrides_with = "vsync")]
+    pub no_vsync: bool,
+
+    /// Draw the edges or the filled faces
+    #[arg(long, global = true, value_enum)]
+    pub render_mode: Option<RenderMode>,
+}
+
+// Scenes are polytopes in the 4OFF format, or point lists in the input format of qhull
+// that are shown as their convex hull.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Explore a scene in a window, the default
+    View {
+        /// The scene to show instead of the one in config.toml, or the tesseract
+        scene: Option<PathBuf>,
+    },
+
+    // Renders offscreen without a window, so it needs a Vulkan driver but no display, e.g. in scripts.
+    /// Render a still of a scene to a PNG file and exit
+    Render {
+        /// The scene file
+        scene: PathBuf,
+
+        /// The PNG file to write
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Write a scene as seen by the default camera, in 3D as OBJ or in 2D as SVG
+    Export {
+        /// The scene file
+        scene: PathBuf,
+
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+
+        /// The file to write, standard output when not given
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Print the counts and the Euler characteristic of a polytope, and check its topology and shape
+    Info {
+        /// The polytope file
+        mesh: PathBuf,
+    },
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExportFormat {
+    Obj,
+    Svg,
+}
+
+impl Cli {
+    // The scene named on the command line, if any.
+    pub fn scene(&self) -> Option<&PathBuf> {
+        match &self.command {
+            Some(Command::View { scene }) => scene.as_ref(),
+            Some(Command::Render { scene, .. }) | Some(Command::Export { scene, .. }) => Some(scene),
+            Some(Command::Info { mesh }) => Some(mesh),
+            None => None,
+        }
+    }
+}
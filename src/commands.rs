@@ -0,0 +1,107 @@
+use crate::cameras::Camera4;
+use crate::cli::ExportFormat;
+use crate::config::Settings;
+use crate::entities::Entity;
+use crate::error::AppError;
+
+use std::fmt::Write;
+use std::path::Path;
+extern crate nalgebra as na;
+
+// The subcommands that work without a window.
+
+fn load(path: &Path) -> Result<Entity, AppError> {
+    Entity::load(path).map_err(|reason| AppError::Scene { path: path.to_path_buf(), reason })
+}
+
+pub fn info(path: &Path) -> Result<(), AppError> {
+    let entity = load(path)?;
+
+    for check in entity.analyze().checks() {
+        let failed = if check.ok == Some(false) { " (failed)" } else { "" };
+        println!("{}: {}{}", check.name, check.value, failed);
+    }
+
+    Ok(())
+}
+
+pub fn export(scene: &Path, format: ExportFormat, output: Option<&Path>, settings: &Settings) -> Result<(), AppError> {
+    let entity = load(scene)?;
+    let (width, height) = (settings.window.width, settings.window.height);
+
+    let mut camera = Camera4::new();
+    camera.camera3.set_aspect(width as f32 / height as f32);
+
+    let text = match format {
+        ExportFormat::Obj => obj(&entity, &camera),
+        ExportFormat::Svg => svg(&entity, &camera, width, height),
+    };
+
+    match output {
+        Some(path) => std::fs::write(path, text)
+            .map_err(|e| AppError::Write { path: path.to_path_buf(), reason: e.to_string() }),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+// The projection into the 3D space of the camera, with vertex colors and the faces as polygons.
+fn obj(entity: &Entity, camera: &Camera4) -> String {
+    let transform = entity.transform();
+    let mut text = String::new();
+
+    for vertex in entity.vertices() {
+        let p = camera.project4(&transform, &vertex.pos);
+        let [r, g, b] = vertex.color;
+        writeln!(text, "v {} {} {} {} {} {}", p.x, p.y, p.z, r, g, b).unwrap();
+    }
+
+    // OBJ counts from 1
+    for face in entity.topology().faces.iter() {
+        let indices: Vec<String> = face.iter().map(|v| (v + 1).to_string()).collect();
+        writeln!(text, "f {}", indices.join(" ")).unwrap();
+    }
+
+    text
+}
+
+// The edges as seen on a screen of `width` by `height`, each in the mean color of its ends.
+fn svg(entity: &Entity, camera: &Camera4, width: u32, height: u32) -> String {
+    let transform = entity.transform();
+    let vertices = entity.vertices();
+    let mut text = String::new();
+
+    writeln!(
+        text,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+        width, height
+    ).unwrap();
+    writeln!(text, r#"<rect width="100%" height="100%" fill="black"/>"#).unwrap();
+
+    let to_screen = |ndc: na::Point3<f32>| {
+        ((ndc.x + 1.0) / 2.0 * width as f32, (ndc.y + 1.0) / 2.0 * height as f32)
+    };
+
+    for &[a, b] in entity.topology().edges.iter() {
+        let (a, b) = (&vertices[a as usize], &vertices[b as usize]);
+
+        // the edges that reach behind the 3D camera are left out
+        let (start, end) = match (camera.project(&transform, &a.pos), camera.project(&transform, &b.pos)) {
+            (Some(start), Some(end)) => (to_screen(start), to_screen(end)),
+            _ => continue,
+        };
+
+        let [r, g, b] = [0, 1, 2].map(|i| ((a.color[i] + b.color[i]) / 2.0 * 255.0).round() as u8);
+        writeln!(
+            text,
+            r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="rgb({},{},{})"/>"#,
+            start.0, start.1, end.0, end.1, r, g, b
+        ).unwrap();
+    }
+
+    writeln!(text, "</svg>").unwrap();
+
+    text
+}
@@ -1,6 +1,449 @@
+use crate::cli::Cli;
 
-pub const WINDOW_TITLE: &'static str = "Cube";
-pub const APP_NAME: &'static str = "Cube";
+use ash::vk;
+use serde::{Deserialize, Serialize};
+use winit::event::VirtualKeyCode;
 
-pub const WINDOW_WIDTH: u32 = 800;
-pub const WINDOW_HEIGHT: u32 = 600;
\ No newline at end of file
+use std::path::{Path, PathBuf};
+
+
+pub const APP_NAME: &str = "hyper-oculus";
+
+pub const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresentMode {
+    // vsync
+    Fifo,
+    // vsync without waiting, the newest frame is shown
+    Mailbox,
+    // no vsync, may tear
+    Immediate,
+}
+
+impl PresentMode {
+    pub const ALL: [PresentMode; 3] = [PresentMode::Fifo, PresentMode::Mailbox, PresentMode::Immediate];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PresentMode::Fifo => "fifo",
+            PresentMode::Mailbox => "mailbox",
+            PresentMode::Immediate => "immediate",
+        }
+    }
+
+    // The modes to try in order. Every device supports FIFO.
+    pub fn fallbacks(&self) -> &'static [vk::PresentModeKHR] {
+        match self {
+            PresentMode::Fifo => &[vk::PresentModeKHR::FIFO],
+            PresentMode::Mailbox => &[vk::PresentModeKHR::MAILBOX, vk::PresentModeKHR::FIFO],
+            PresentMode::Immediate => &[
+                vk::PresentModeKHR::IMMEDIATE,
+                vk::PresentModeKHR::MAILBOX,
+                vk::PresentModeKHR::FIFO,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderMode {
+    // the edges
+    Wireframe,
+    // the 2-faces, filled
+    Solid,
+}
+
+impl RenderMode {
+    pub const ALL: [RenderMode; 2] = [RenderMode::Wireframe, RenderMode::Solid];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RenderMode::Wireframe => "wireframe",
+            RenderMode::Solid => "solid",
+        }
+    }
+
+    pub fn polygon_mode(&self) -> vk::PolygonMode {
+        match self {
+            RenderMode::Wireframe => vk::PolygonMode::LINE,
+            RenderMode::Solid => vk::PolygonMode::FILL,
+        }
+    }
+}
+
+pub const MAX_FRAMES_IN_FLIGHT: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    // in logical pixels, the size of the window when it is not fullscreen
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            fullscreen: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Controls {
+    // units per second of the 3D and the 4D camera
+    pub move_speed3: f32,
+    pub move_speed4: f32,
+    // degrees per pixel of mouse motion
+    pub turn_speed: f32,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            move_speed3: 5.0,
+            move_speed4: 1.0,
+            turn_speed: 0.1,
+        }
+    }
+}
+
+// Keys are named as in winit's `VirtualKeyCode`, e.g. "W", "Space" or "F12".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub forward: VirtualKeyCode,
+    pub back: VirtualKeyCode,
+    pub right: VirtualKeyCode,
+    pub left: VirtualKeyCode,
+    pub up: VirtualKeyCode,
+    pub down: VirtualKeyCode,
+
+    // along the axes of the 4D camera
+    pub x_plus: VirtualKeyCode,
+    pub x_minus: VirtualKeyCode,
+    pub y_plus: VirtualKeyCode,
+    pub y_minus: VirtualKeyCode,
+    pub z_plus: VirtualKeyCode,
+    pub z_minus: VirtualKeyCode,
+    pub w_plus: VirtualKeyCode,
+    pub w_minus: VirtualKeyCode,
+
+    pub grab_cursor: VirtualKeyCode,
+    pub record: VirtualKeyCode,
+    pub screenshot: VirtualKeyCode,
+    pub quit: VirtualKeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            forward: VirtualKeyCode::W,
+            back: VirtualKeyCode::S,
+            right: VirtualKeyCode::D,
+            left: VirtualKeyCode::A,
+            up: VirtualKeyCode::C,
+            down: VirtualKeyCode::V,
+
+            x_plus: VirtualKeyCode::Y,
+            x_minus: VirtualKeyCode::H,
+            y_plus: VirtualKeyCode::U,
+            y_minus: VirtualKeyCode::J,
+            z_plus: VirtualKeyCode::I,
+            z_minus: VirtualKeyCode::K,
+            w_plus: VirtualKeyCode::O,
+            w_minus: VirtualKeyCode::L,
+
+            grab_cursor: VirtualKeyCode::Tab,
+            record: VirtualKeyCode::F9,
+            screenshot: VirtualKeyCode::F12,
+            quit: VirtualKeyCode::Escape,
+        }
+    }
+}
+
+// User settings: the defaults, overridden by `config.toml` in the user's config directory,
+// overridden by the command line. Missing entries take their default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub present_mode: PresentMode,
+    pub frames_in_flight: usize,
+    pub render_mode: RenderMode,
+    // a polytope in the 4OFF format or a point list, the tesseract when there is none
+    pub scene: Option<PathBuf>,
+    pub window: WindowSettings,
+    pub controls: Controls,
+    pub keys: KeyBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            present_mode: PresentMode::Mailbox,
+            frames_in_flight: 2,
+            render_mode: RenderMode::Wireframe,
+            scene: None,
+            window: WindowSettings::default(),
+            controls: Controls::default(),
+            keys: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    // `HYPER_OCULUS_CONFIG`, or `hyper-oculus/config.toml` in the platform's config directory.
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("HYPER_OCULUS_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+
+        let directory = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(directory.join("hyper-oculus").join("config.toml"))
+    }
+
+    // The defaults are used when there is no file, and for a broken one after a warning.
+    pub fn load() -> Self {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Self::default(),
+        };
+
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => return Self::default(),
+        };
+
+        let mut settings: Self = toml::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring {}: {}", path.display(), e);
+            Self::default()
+        });
+        settings.frames_in_flight = settings.frames_in_flight.clamp(1, MAX_FRAMES_IN_FLIGHT);
+
+        settings
+    }
+
+    pub fn override_with(&mut self, cli: &Cli) {
+        if let Some(width) = cli.width {
+            self.window.width = width;
+        }
+        if let Some(height) = cli.height {
+            self.window.height = height;
+        }
+        if cli.fullscreen {
+            self.window.fullscreen = true;
+        }
+        if cli.windowed {
+            self.window.fullscreen = false;
+        }
+        if cli.vsync {
+            self.present_mode = PresentMode::Fifo;
+        }
+        if cli.no_vsync {
+            self.present_mode = PresentMode::Immediate;
+        }
+        if let Some(render_mode) = cli.render_mode {
+            self.render_mode = render_mode;
+        }
+        if let Some(scene) = cli.scene() {
+            self.scene = Some(scene.clone());
+        }
+    }
+
+    // Write the settings changed since `started` back to the file. The others keep what the
+    // file says, so that command line flags are not saved, and a broken file is left alone.
+    pub fn save(&self, started: &Settings) {
+        if self == started {
+            return;
+        }
+
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Err(e) = self.write(started, &path) {
+            log::warn!("Could not save the settings to {}: {}", path.display(), e);
+        }
+    }
+
+    fn write(&self, started: &Settings, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = match std::fs::read_to_string(path) {
+            Ok(text) => text.parse()?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => toml::Value::Table(Default::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        merge_changes(&mut file, &toml::Value::try_from(started)?, &toml::Value::try_from(self)?);
+
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(&file)?)?;
+
+        Ok(())
+    }
+
+    // `present_mode` is what the swapchain actually uses.
+    pub fn display_ui(&mut self, ui: &mut egui::Ui, present_mode: Option<vk::PresentModeKHR>) {
+        ui.horizontal(|ui| {
+            for mode in PresentMode::ALL {
+                ui.radio_value(&mut self.present_mode, mode, mode.name());
+            }
+        });
+        if let Some(present_mode) = present_mode {
+            ui.label(format!("presenting with {:?}", present_mode));
+        }
+
+        ui.add(egui::Slider::new(&mut self.frames_in_flight, 1..=MAX_FRAMES_IN_FLIGHT).text("frames in flight"));
+
+        ui.horizontal(|ui| {
+            for mode in RenderMode::ALL {
+                ui.radio_value(&mut self.render_mode, mode, mode.name());
+            }
+        });
+        ui.checkbox(&mut self.window.fullscreen, "fullscreen");
+    }
+
+    pub fn controls_ui(&mut self, ui: &mut egui::Ui) {
+        let controls = &mut self.controls;
+
+        ui.add(egui::Slider::new(&mut controls.move_speed3, 0.1..=50.0).logarithmic(true).text("3D speed"));
+        ui.add(egui::Slider::new(&mut controls.move_speed4, 0.1..=50.0).logarithmic(true).text("4D speed"));
+        ui.add(egui::Slider::new(&mut controls.turn_speed, 0.01..=1.0).logarithmic(true).text("turn speed"));
+
+        if let Some(path) = Self::path() {
+            ui.label(format!("Keys are bound in {}", path.display()));
+        }
+    }
+}
+
+// Copy the values of `current` that differ from `started` into `file`, table by table.
+fn merge_changes(file: &mut toml::Value, started: &toml::Value, current: &toml::Value) {
+    let (file, current) = match (file, current) {
+        (toml::Value::Table(file), toml::Value::Table(current)) => (file, current),
+        (file, current) => {
+            if started != current {
+                *file = current.clone();
+            }
+            return;
+        }
+    };
+
+    for (key, value) in current {
+        let before = match started.get(key) {
+            Some(before) => before,
+            None => {
+                file.insert(key.clone(), value.clone());
+                continue;
+            }
+        };
+
+        match file.get_mut(key) {
+            Some(entry) => merge_changes(entry, before, value),
+            // a table the file doesn't have yet gets only the changed values
+            None if value.is_table() => {
+                let mut entry = toml::Value::Table(Default::default());
+                merge_changes(&mut entry, before, value);
+
+                if entry.as_table().is_some_and(|table| !table.is_empty()) {
+                    file.insert(key.clone(), entry);
+                }
+            }
+            None => {
+                if before != value {
+                    file.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    const FILE: &str = r#"
+        # a comment, which is lost on saving
+        frames_in_flight = 3
+        note = "not a setting"
+
+        [window]
+        width = 1024
+    "#;
+
+    // The settings as started with `arguments`, one of them then changed by `edit`.
+    fn merged(arguments: &[&str], edit: impl FnOnce(&mut Settings)) -> toml::Value {
+        let mut file: toml::Value = FILE.parse().unwrap();
+
+        let mut started: Settings = toml::from_str(FILE).unwrap();
+        started.override_with(&Cli::try_parse_from(arguments).unwrap());
+
+        let mut current = started.clone();
+        edit(&mut current);
+
+        merge_changes(&mut file, &toml::Value::try_from(&started).unwrap(), &toml::Value::try_from(&current).unwrap());
+        file
+    }
+
+    #[test]
+    fn writes_only_the_changes() {
+        let file = merged(&["hyper-oculus"], |settings| settings.controls.move_speed3 = 8.0);
+
+        let expected: toml::Value = r#"
+            frames_in_flight = 3
+            note = "not a setting"
+
+            [window]
+            width = 1024
+
+            [controls]
+            move_speed3 = 8.0
+        "#.parse().unwrap();
+        assert_eq!(file, expected);
+    }
+
+    #[test]
+    fn leaves_out_the_command_line() {
+        let file = merged(&["hyper-oculus", "--height", "300", "--render-mode", "solid"], |settings| {
+            settings.window.fullscreen = true;
+        });
+
+        let expected: toml::Value = r#"
+            frames_in_flight = 3
+            note = "not a setting"
+
+            [window]
+            width = 1024
+            fullscreen = true
+        "#.parse().unwrap();
+        assert_eq!(file, expected);
+    }
+
+    #[test]
+    fn writes_changes_to_command_line_values() {
+        let file = merged(&["hyper-oculus", "--width", "640"], |settings| settings.window.width = 800);
+
+        assert_eq!(file["window"]["width"].as_integer(), Some(800));
+    }
+
+    #[test]
+    fn writes_nothing_without_changes() {
+        let file = merged(&["hyper-oculus", "--no-vsync"], |_| ());
+
+        assert_eq!(file, FILE.parse().unwrap());
+    }
+}
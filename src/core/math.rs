@@ -38,6 +38,14 @@ pub fn perspective4(n: f32, f: f32) -> na::Matrix5<f32> {
     return m
 }
 
+// `perspective4` followed by the orthographic projection of its view volume,
+// with the vertical field of view `fovy`.
+pub fn perspective4_short(n: f32, f: f32, fovy: f32) -> na::Matrix5<f32> {
+    let half_width = n.abs() / 2.0 * (fovy / 2.0).tan();
+
+    ortho4_short(n, f, half_width) * perspective4(n, f)
+}
+
 pub fn ortho4(
     x_near: f32, x_far: f32,
     y_near: f32, y_far: f32,
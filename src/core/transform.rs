@@ -0,0 +1,36 @@
+use super::math::{self, translate4};
+
+extern crate nalgebra as na;
+
+// Names of the six rotation planes, in the order of `Transform4::angles`.
+pub const PLANES: [&str; 6] = ["xy", "yz", "zx", "xw", "yw", "zw"];
+
+// A rigid 4D transform: a rotation by an angle in each of the six planes, then a translation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform4 {
+    pub translation: na::Vector4<f32>,
+    pub angles: [f32; 6],
+}
+
+impl Default for Transform4 {
+    fn default() -> Self {
+        Self {
+            translation: na::Vector4::zeros(),
+            angles: [0.0; 6],
+        }
+    }
+}
+
+impl Transform4 {
+    pub fn matrix(&self) -> na::Matrix5<f32> {
+        let [xy, yz, zx, xw, yw, zw] = self.angles;
+
+        translate4(&na::Matrix5::identity(), &self.translation)
+            * math::ratate4_xy(xy)
+            * math::ratate4_yz(yz)
+            * math::ratate4_zx(zx)
+            * math::ratate4_xw(xw)
+            * math::ratate4_yw(yw)
+            * math::ratate4_zw(zw)
+    }
+}
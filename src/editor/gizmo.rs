@@ -0,0 +1,202 @@
+use crate::cameras::Camera4;
+use crate::core::transform::{Transform4, PLANES};
+use crate::ui::to_screen;
+
+extern crate nalgebra as na;
+
+// Length of the gizmo axes in world units.
+const AXIS_LENGTH: f32 = 0.75;
+const HANDLE_RADIUS: f32 = 7.0;
+// Radians per pixel when dragging a rotation handle.
+const ROTATION_SPEED: f32 = 0.01;
+
+const AXES: [&str; 4] = ["x", "y", "z", "w"];
+const AXIS_COLORS: [egui::Color32; 4] = [
+    egui::Color32::from_rgb(230, 60, 60),
+    egui::Color32::from_rgb(60, 200, 60),
+    egui::Color32::from_rgb(70, 110, 240),
+    egui::Color32::from_rgb(210, 70, 210),
+];
+
+#[derive(Debug, Copy, Clone)]
+struct Edit {
+    entity: usize,
+    before: Transform4,
+    after: Transform4,
+}
+
+// Edits the pose of the selected entity with on-screen handles and numeric fields,
+// and keeps the undo history of those edits.
+#[derive(Default)]
+pub struct Gizmo {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    // the edit in progress, e.g. while a handle is dragged
+    current: Option<Edit>,
+    interacting: bool,
+    undo_requested: bool,
+    redo_requested: bool,
+}
+
+impl Gizmo {
+    pub fn fields_ui(&mut self, ui: &mut egui::Ui, pose: &mut Transform4) {
+        egui::Grid::new("gizmo_fields").show(ui, |ui| {
+            for (axis, name) in AXES.iter().enumerate() {
+                ui.label(*name);
+                let response = ui.add(egui::DragValue::new(&mut pose.translation[axis]).speed(0.01));
+                self.track(&response);
+
+                ui.label(PLANES[axis]);
+                let response = ui.drag_angle(&mut pose.angles[axis]);
+                self.track(&response);
+                ui.end_row();
+            }
+
+            for (plane, angle) in PLANES.iter().zip(pose.angles.iter_mut()).skip(4) {
+                ui.label("");
+                ui.label("");
+                ui.label(*plane);
+                let response = ui.drag_angle(angle);
+                self.track(&response);
+                ui.end_row();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            self.undo_requested |= ui.add_enabled(!self.undo.is_empty(), egui::Button::new("Undo")).clicked();
+            self.redo_requested |= ui.add_enabled(!self.redo.is_empty(), egui::Button::new("Redo")).clicked();
+        });
+    }
+
+    // Translation handles at the tips of the world axes and rotation handles
+    // between each pair of tips, drawn around the origin of the pose inside `screen`.
+    pub fn handles(&mut self, ctx: &egui::Context, camera: &Camera4, pose: &mut Transform4, screen: egui::Rect) {
+        let identity = na::Matrix5::identity();
+        let project = |p: na::Vector4<f32>| to_screen(camera.project(&identity, &[p[0], p[1], p[2], p[3]]), screen);
+
+        let origin = pose.translation;
+        let center = match project(origin) {
+            Some(center) => center,
+            None => return,
+        };
+        let tips: Vec<Option<egui::Pos2>> = (0..4)
+            .map(|axis| project(origin + na::Vector4::ith(axis, AXIS_LENGTH)))
+            .collect();
+
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("gizmo"),
+        ));
+
+        for (axis, tip) in tips.iter().enumerate() {
+            let tip = match tip {
+                Some(tip) => *tip,
+                None => continue,
+            };
+
+            painter.line_segment([center, tip], egui::Stroke::new(2.0, AXIS_COLORS[axis]));
+            let response = self.handle(ctx, ("gizmo_axis", axis), tip, AXES[axis], AXIS_COLORS[axis]);
+
+            // move along the axis by the part of the drag in its on-screen direction
+            let direction = tip - center;
+            if response.dragged() && direction.length_sq() > 1.0 {
+                pose.translation[axis] += response.drag_delta().dot(direction) / direction.length_sq() * AXIS_LENGTH;
+            }
+        }
+
+        let planes = [(0, 1), (1, 2), (2, 0), (0, 3), (1, 3), (2, 3)];
+        for (plane, (a, b)) in planes.into_iter().enumerate() {
+            if let (Some(a), Some(b)) = (tips[a], tips[b]) {
+                let position = center + ((a - center) + (b - center)) * 0.5;
+                let response = self.handle(ctx, ("gizmo_plane", plane), position, PLANES[plane], egui::Color32::GRAY);
+
+                if response.dragged() {
+                    pose.angles[plane] += response.drag_delta().x * ROTATION_SPEED;
+                }
+            }
+        }
+    }
+
+    fn handle(
+        &mut self,
+        ctx: &egui::Context,
+        id: impl std::hash::Hash,
+        position: egui::Pos2,
+        text: &str,
+        color: egui::Color32,
+    ) -> egui::Response {
+        let size = egui::Vec2::splat(HANDLE_RADIUS * 2.0);
+
+        // every handle is its own small area, so egui only claims the pointer over the handles
+        let response = egui::Area::new(egui::Id::new(id))
+            .current_pos(position - size / 2.0)
+            .order(egui::Order::Middle)
+            .show(ctx, |ui| {
+                let (rect, response) = ui.allocate_exact_size(size, egui::Sense::drag());
+                let fill = if response.hovered() || response.dragged() { egui::Color32::WHITE } else { color };
+                ui.painter().circle_filled(rect.center(), HANDLE_RADIUS, fill);
+                response.on_hover_text(text)
+            })
+            .inner;
+
+        self.track(&response);
+        response
+    }
+
+    fn track(&mut self, response: &egui::Response) {
+        self.interacting |= response.dragged() || response.has_focus();
+    }
+
+    // Record the edit of an entity's pose from `before` to `after`, and handle undo and redo.
+    // Returns the entity and pose to apply, if anything changed.
+    pub fn finish(
+        &mut self,
+        ctx: &egui::Context,
+        edited: Option<(usize, Transform4, Transform4)>,
+    ) -> Option<(usize, Transform4)> {
+        let interacting = std::mem::take(&mut self.interacting);
+
+        if let Some((entity, before, after)) = edited {
+            if after != before {
+                let edit = self.current.get_or_insert(Edit { entity, before, after });
+                edit.after = after;
+            }
+
+            if interacting {
+                return (after != before).then_some((entity, after));
+            }
+        }
+
+        if let Some(edit) = self.current.take() {
+            if edit.before != edit.after {
+                self.undo.push(edit);
+                self.redo.clear();
+            }
+            return Some((edit.entity, edit.after));
+        }
+
+        let mut undo = std::mem::take(&mut self.undo_requested);
+        let mut redo = std::mem::take(&mut self.redo_requested);
+
+        if !ctx.wants_keyboard_input() {
+            let input = ctx.input();
+            let command = input.modifiers.command;
+            let redo_key = input.key_pressed(egui::Key::Y) || (input.modifiers.shift && input.key_pressed(egui::Key::Z));
+
+            redo |= command && redo_key;
+            undo |= command && !redo_key && input.key_pressed(egui::Key::Z);
+        }
+
+        if undo {
+            let edit = self.undo.pop()?;
+            self.redo.push(edit);
+            Some((edit.entity, edit.before))
+        } else if redo {
+            let edit = self.redo.pop()?;
+            self.undo.push(edit);
+            Some((edit.entity, edit.after))
+        } else {
+            None
+        }
+    }
+}
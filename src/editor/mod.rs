@@ -0,0 +1,5 @@
+mod selection;
+pub use selection::Selection;
+
+mod gizmo;
+pub use gizmo::Gizmo;
@@ -0,0 +1,222 @@
+use crate::cameras::Camera4;
+use crate::entities::Entity;
+use crate::ui::to_screen;
+
+extern crate nalgebra as na;
+
+// Pick distances in pixels.
+const VERTEX_RADIUS: f32 = 8.0;
+const EDGE_DISTANCE: f32 = 5.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Element {
+    Vertex(usize),
+    Edge(usize),
+    Cell(usize),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub entity: usize,
+    pub element: Element,
+}
+
+impl Selection {
+    // Find the element under `cursor`, with both in the pixel space of `screen`.
+    // Vertices win over edges and edges over cells; among cells the nearest one wins.
+    pub fn pick(camera: &Camera4, entities: &[Entity], cursor: egui::Pos2, screen: egui::Rect) -> Option<Self> {
+        let mut vertex = None;
+        let mut edge = None;
+        let mut cell = None;
+
+        for (entity_index, entity) in entities.iter().enumerate() {
+            let transform = entity.transform();
+            let projected: Vec<Option<(egui::Pos2, f32)>> = entity
+                .vertices()
+                .iter()
+                .map(|v| {
+                    let ndc = camera.project(&transform, &v.pos);
+                    to_screen(ndc, screen).map(|p| (p, ndc.unwrap()[2]))
+                })
+                .collect();
+
+            for (i, p) in projected.iter().enumerate() {
+                if let Some((p, depth)) = p {
+                    let distance = p.distance(cursor);
+                    if distance < VERTEX_RADIUS && closer(&vertex, distance, *depth) {
+                        vertex = Some((distance, *depth, Selection { entity: entity_index, element: Element::Vertex(i) }));
+                    }
+                }
+            }
+
+            let topology = entity.topology();
+            for (i, &[a, b]) in topology.edges.iter().enumerate() {
+                if let (Some((a, da)), Some((b, db))) = (projected[a as usize], projected[b as usize]) {
+                    let distance = segment_distance(cursor, a, b);
+                    let depth = (da + db) / 2.0;
+                    if distance < EDGE_DISTANCE && closer(&edge, distance, depth) {
+                        edge = Some((distance, depth, Selection { entity: entity_index, element: Element::Edge(i) }));
+                    }
+                }
+            }
+
+            for i in 0..topology.cells.len() {
+                let points: Option<Vec<(egui::Pos2, f32)>> = topology
+                    .cell_vertices(i)
+                    .iter()
+                    .map(|&v| projected[v as usize])
+                    .collect();
+
+                // cells partly behind the camera can't be picked
+                let points = match points {
+                    Some(points) => points,
+                    None => continue,
+                };
+
+                let depth = points.iter().map(|p| p.1).sum::<f32>() / points.len() as f32;
+                let outline = hull2(points.iter().map(|p| p.0).collect());
+                if contains(&outline, cursor) && closer(&cell, 0.0, depth) {
+                    cell = Some((0.0, depth, Selection { entity: entity_index, element: Element::Cell(i) }));
+                }
+            }
+        }
+
+        vertex.or(edge).or(cell).map(|(_, _, selection)| selection)
+    }
+
+    // The selected element in world space.
+    pub fn ui(&self, ui: &mut egui::Ui, entities: &[Entity]) {
+        let entity = match entities.get(self.entity) {
+            Some(entity) => entity,
+            None => return,
+        };
+
+        let transform = entity.transform();
+        let topology = entity.topology();
+        let world = |i: u32| {
+            let pos = entity.vertices()[i as usize].pos;
+            let p = transform * na::Vector5::new(pos[0], pos[1], pos[2], pos[3], 1.0);
+            na::Point4::from(p.fixed_rows::<4>(0) / p[4])
+        };
+        let coordinates = |p: na::Point4<f32>| format!("({:.3}, {:.3}, {:.3}, {:.3})", p[0], p[1], p[2], p[3]);
+
+        ui.label(format!("entity: {}", self.entity));
+        match self.element {
+            Element::Vertex(i) => {
+                match entity.vertex_name(i) {
+                    Some(name) => ui.label(format!("vertex {} ({})", i, name)),
+                    None => ui.label(format!("vertex {}", i)),
+                };
+                ui.label(coordinates(world(i as u32)));
+            }
+            Element::Edge(i) => {
+                let [a, b] = topology.edges[i].map(world);
+                ui.label(format!("edge {}: {} - {}", i, topology.edges[i][0], topology.edges[i][1]));
+                ui.label(format!("from {}", coordinates(a)));
+                ui.label(format!("to {}", coordinates(b)));
+                ui.label(format!("length: {:.3}", (b - a).norm()));
+            }
+            Element::Cell(i) => {
+                let vertices = topology.cell_vertices(i);
+                let center = vertices
+                    .iter()
+                    .fold(na::Point4::origin(), |c, &v| c + world(v).coords / vertices.len() as f32);
+                ui.label(format!("cell {}: {} faces, {} vertices", i, topology.cells[i].len(), vertices.len()));
+                ui.label(format!("center {}", coordinates(center)));
+            }
+        }
+    }
+
+    // Highlight the selected element in the viewport.
+    pub fn paint(&self, ctx: &egui::Context, camera: &Camera4, entities: &[Entity], screen: egui::Rect) {
+        let entity = match entities.get(self.entity) {
+            Some(entity) => entity,
+            None => return,
+        };
+
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("selection"),
+        )).with_clip_rect(screen);
+        let transform = entity.transform();
+        let topology = entity.topology();
+        let project = |i: u32| to_screen(camera.project(&transform, &entity.vertices()[i as usize].pos), screen);
+        let stroke = egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 160, 0));
+
+        let edges = match self.element {
+            Element::Vertex(i) => {
+                if let Some(p) = project(i as u32) {
+                    painter.circle_stroke(p, VERTEX_RADIUS, stroke);
+                }
+                Vec::new()
+            }
+            Element::Edge(i) => vec![i],
+            Element::Cell(i) => topology.cell_edges(i),
+        };
+
+        for e in edges {
+            let [a, b] = topology.edges[e];
+            if let (Some(a), Some(b)) = (project(a), project(b)) {
+                painter.line_segment([a, b], stroke);
+            }
+        }
+    }
+}
+
+fn closer(best: &Option<(f32, f32, Selection)>, distance: f32, depth: f32) -> bool {
+    match best {
+        Some((best_distance, best_depth, _)) => (distance, depth) < (*best_distance, *best_depth),
+        None => true,
+    }
+}
+
+fn segment_distance(p: egui::Pos2, a: egui::Pos2, b: egui::Pos2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_sq() > 0.0 {
+        ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    p.distance(a + ab * t)
+}
+
+fn cross2(o: egui::Pos2, a: egui::Pos2, b: egui::Pos2) -> f32 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+// Monotone chain convex hull, with every turn having a positive `cross2`.
+fn hull2(mut points: Vec<egui::Pos2>) -> Vec<egui::Pos2> {
+    points.retain(|p| p.x.is_finite() && p.y.is_finite());
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    if points.len() < 3 {
+        return points;
+    }
+
+    let mut hull: Vec<egui::Pos2> = Vec::with_capacity(points.len() * 2);
+
+    // lower chain
+    for &p in points.iter() {
+        while hull.len() >= 2 && cross2(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+
+    // upper chain
+    let lower = hull.len() + 1;
+    for &p in points.iter().rev().skip(1) {
+        while hull.len() >= lower && cross2(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+
+    hull.pop();
+    hull
+}
+
+fn contains(hull: &[egui::Pos2], p: egui::Pos2) -> bool {
+    hull.len() >= 3
+        && (0..hull.len()).all(|i| cross2(hull[i], hull[(i + 1) % hull.len()], p) >= 0.0)
+}
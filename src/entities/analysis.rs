@@ -0,0 +1,282 @@
+use super::Entity;
+use crate::core::math::cross4;
+
+use std::collections::HashMap;
+extern crate nalgebra as na;
+
+// Tolerance relative to the size of the entity.
+const EPSILON: f32 = 1e-4;
+
+// One line of a `Report`. `ok` is set for the checks a closed polytope has to pass,
+// and `None` for counts and properties.
+pub struct Check {
+    pub name: &'static str,
+    pub value: String,
+    pub ok: Option<bool>,
+}
+
+pub struct Report {
+    pub vertices: usize,
+    pub edges: usize,
+    pub faces: usize,
+    pub cells: usize,
+    // V - E + F - C, 0 for the surface of a 4-polytope
+    pub euler: i64,
+    // the faces that are not in exactly two cells, with the number of cells they are in
+    pub open_faces: Vec<(usize, usize)>,
+    pub shortest_edge: f32,
+    pub longest_edge: f32,
+    // `None` without cells
+    pub convex: Option<bool>,
+    // the edges, faces and cells at a vertex, which are the vertices, edges and faces of its
+    // vertex figure, and how many vertices have that figure
+    pub vertex_figures: Vec<([usize; 3], usize)>,
+}
+
+impl Entity {
+    pub fn analyze(&self) -> Report {
+        let topology = self.topology();
+        let points: Vec<na::Point4<f32>> = self.vertices().iter().map(|v| na::Point4::from(v.pos)).collect();
+
+        let size = points.iter().map(|p| p.coords.amax()).fold(1.0, f32::max);
+        let epsilon = EPSILON * size;
+
+        let mut cell_counts = vec![0; topology.faces.len()];
+        for cell in topology.cells.iter() {
+            for &face in cell.iter() {
+                cell_counts[face] += 1;
+            }
+        }
+        let open_faces = cell_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count != 2)
+            .map(|(face, &count)| (face, count))
+            .collect();
+
+        let lengths = topology
+            .edges
+            .iter()
+            .map(|&[a, b]| (points[a as usize] - points[b as usize]).norm());
+        let shortest_edge = lengths.clone().fold(f32::MAX, f32::min);
+        let longest_edge = lengths.fold(0.0, f32::max);
+
+        let cell_vertices: Vec<Vec<u32>> = (0..topology.cells.len()).map(|cell| topology.cell_vertices(cell)).collect();
+
+        // Every cell has to be flat, with all vertices on one side of its hyperplane.
+        let convex = (!cell_vertices.is_empty()).then(|| {
+            cell_vertices.iter().all(|cell| {
+                let cell_points: Vec<_> = cell.iter().map(|&v| points[v as usize]).collect();
+
+                match hyperplane(&cell_points, epsilon) {
+                    Some((normal, offset)) => {
+                        let distances: Vec<f32> = points.iter().map(|p| normal.dot(&p.coords) - offset).collect();
+
+                        cell_points.iter().all(|p| (normal.dot(&p.coords) - offset).abs() < epsilon)
+                            && (distances.iter().all(|&d| d < epsilon) || distances.iter().all(|&d| d > -epsilon))
+                    }
+                    None => false,
+                }
+            })
+        });
+
+        let mut figures = vec![[0; 3]; points.len()];
+        for &[a, b] in topology.edges.iter() {
+            figures[a as usize][0] += 1;
+            figures[b as usize][0] += 1;
+        }
+        for face in topology.faces.iter() {
+            for &v in face.iter() {
+                figures[v as usize][1] += 1;
+            }
+        }
+        for cell in cell_vertices.iter() {
+            for &v in cell.iter() {
+                figures[v as usize][2] += 1;
+            }
+        }
+
+        let mut vertex_figures: HashMap<[usize; 3], usize> = HashMap::new();
+        for figure in figures {
+            *vertex_figures.entry(figure).or_default() += 1;
+        }
+        let mut vertex_figures: Vec<_> = vertex_figures.into_iter().collect();
+        vertex_figures.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let [vertices, edges, faces, cells] = [
+            points.len(),
+            topology.edges.len(),
+            topology.faces.len(),
+            topology.cells.len(),
+        ];
+
+        Report {
+            vertices,
+            edges,
+            faces,
+            cells,
+            euler: vertices as i64 - edges as i64 + faces as i64 - cells as i64,
+            open_faces,
+            shortest_edge,
+            longest_edge,
+            convex,
+            vertex_figures,
+        }
+    }
+}
+
+impl Report {
+    pub fn checks(&self) -> Vec<Check> {
+        let count = |name, count: usize| Check { name, value: count.to_string(), ok: None };
+
+        let open_faces = match self.open_faces.as_slice() {
+            [] => "all".to_string(),
+            open => {
+                let listed: Vec<String> = open
+                    .iter()
+                    .take(5)
+                    .map(|(face, cells)| format!("face {} in {}", face, cells))
+                    .collect();
+                let more = if open.len() > 5 { ", ..." } else { "" };
+
+                format!("{} are not: {}{}", open.len(), listed.join(", "), more)
+            }
+        };
+
+        let edge_lengths = if self.edges == 0 {
+            "no edges".to_string()
+        } else if self.longest_edge - self.shortest_edge <= EPSILON * self.longest_edge {
+            format!("uniform, {}", self.longest_edge)
+        } else {
+            format!("from {} to {}", self.shortest_edge, self.longest_edge)
+        };
+
+        let vertex_figure = match self.vertex_figures.as_slice() {
+            [] => "no vertices".to_string(),
+            [(figure, _)] => format!("{} at every vertex", figure_name(figure)),
+            figures => {
+                let kinds: Vec<String> = figures
+                    .iter()
+                    .map(|(figure, count)| format!("{} x {}", count, figure_name(figure)))
+                    .collect();
+
+                kinds.join(", ")
+            }
+        };
+
+        vec![
+            count("vertices", self.vertices),
+            count("edges", self.edges),
+            count("faces", self.faces),
+            count("cells", self.cells),
+            Check { name: "euler characteristic", value: self.euler.to_string(), ok: Some(self.euler == 0) },
+            Check { name: "faces in two cells", value: open_faces, ok: Some(self.open_faces.is_empty()) },
+            Check {
+                name: "convex",
+                value: match self.convex {
+                    Some(true) => "yes",
+                    Some(false) => "no",
+                    None => "no cells",
+                }.to_string(),
+                ok: self.convex,
+            },
+            Check { name: "edge lengths", value: edge_lengths, ok: None },
+            Check { name: "vertex figure", value: vertex_figure, ok: None },
+        ]
+    }
+}
+
+// The Platonic solids by their vertex, edge and face counts.
+fn figure_name(figure: &[usize; 3]) -> String {
+    match figure {
+        [4, 6, 4] => "tetrahedron".to_string(),
+        [8, 12, 6] => "cube".to_string(),
+        [6, 12, 8] => "octahedron".to_string(),
+        [20, 30, 12] => "dodecahedron".to_string(),
+        [12, 30, 20] => "icosahedron".to_string(),
+        [v, e, f] => format!("({} vertices, {} edges, {} faces)", v, e, f),
+    }
+}
+
+// The hyperplane through points spanning three dimensions, as a unit normal and its offset from the origin.
+fn hyperplane(points: &[na::Point4<f32>], epsilon: f32) -> Option<(na::Vector4<f32>, f32)> {
+    let origin = points.first()?;
+    let mut basis: Vec<na::Vector4<f32>> = Vec::with_capacity(3);
+
+    for p in points.iter().skip(1) {
+        let mut v = p - origin;
+        for b in basis.iter() {
+            v -= b * b.dot(&v);
+        }
+
+        if v.norm() > epsilon {
+            basis.push(v.normalize());
+            if basis.len() == 3 {
+                let normal = cross4(&basis[0], &basis[1], &basis[2]).normalize();
+                return Some((normal, normal.dot(&origin.coords)));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<'a>(checks: &'a [Check], name: &str) -> &'a Check {
+        checks.iter().find(|check| check.name == name).unwrap()
+    }
+
+    #[test]
+    fn tesseract() {
+        let report = Entity::hypercube().analyze();
+
+        assert_eq!([report.vertices, report.edges, report.faces, report.cells], [16, 32, 24, 8]);
+        assert_eq!(report.euler, 0);
+        assert!(report.open_faces.is_empty());
+        assert_eq!(report.convex, Some(true));
+        assert_eq!(report.vertex_figures, vec![([4, 6, 4], 16)]);
+
+        let checks = report.checks();
+        assert!(checks.iter().all(|check| check.ok != Some(false)));
+        assert_eq!(check(&checks, "vertex figure").value, "tetrahedron at every vertex");
+        assert_eq!(check(&checks, "edge lengths").value, "uniform, 1");
+    }
+
+    #[test]
+    fn pentachoron() {
+        let report = Entity::simplex().analyze();
+
+        assert_eq!([report.vertices, report.edges, report.faces, report.cells], [5, 10, 10, 5]);
+        assert_eq!(report.euler, 0);
+        assert!(report.open_faces.is_empty());
+        assert_eq!(report.convex, Some(true));
+        assert_eq!(report.vertex_figures, vec![([4, 6, 4], 5)]);
+
+        let checks = report.checks();
+        assert!(checks.iter().all(|check| check.ok != Some(false)));
+        assert_eq!(check(&checks, "vertex figure").value, "tetrahedron at every vertex");
+    }
+
+    #[test]
+    fn open_and_concave() {
+        let tesseract = Entity::hypercube();
+
+        // without its last cell the faces of that cube are in one cell only
+        let mut topology = tesseract.topology().clone();
+        let removed = topology.cells.pop().unwrap();
+        let open = Entity::new(tesseract.vertices(), topology).analyze();
+        assert_eq!(open.euler, 1);
+        assert_eq!(open.open_faces, removed.iter().map(|&face| (face, 1)).collect::<Vec<_>>());
+        assert_eq!(check(&open.checks(), "faces in two cells").ok, Some(false));
+
+        // pushing a vertex inwards bends the cells around it
+        let mut vertices = tesseract.vertices();
+        vertices[0].pos = [-0.2, -0.2, -0.2, -0.2];
+        let dented = Entity::new(vertices, tesseract.topology().clone()).analyze();
+        assert_eq!(dented.euler, 0);
+        assert_eq!(dented.convex, Some(false));
+    }
+}
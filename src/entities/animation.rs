@@ -0,0 +1,73 @@
+use crate::core::transform::Transform4;
+
+extern crate nalgebra as na;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Keyframe {
+    pub time: f32,
+    pub pose: Transform4,
+}
+
+// Motion of an entity over time, on top of its pose:
+// constant angular velocities in the six rotation planes, a constant velocity
+// and a looping keyframe track.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Animation {
+    // radians per second, in the order of `Transform4::angles`
+    pub spin: [f32; 6],
+    pub velocity: na::Vector4<f32>,
+    // sorted by time; the track loops after the last keyframe
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl Animation {
+    // The classic rotating tesseract: a double rotation in the xw and yz planes.
+    pub fn double_rotation(rate: f32) -> Self {
+        Self {
+            spin: [0.0, rate, 0.0, rate, 0.0, 0.0],
+            ..Default::default()
+        }
+    }
+
+    pub fn add_keyframe(&mut self, time: f32, pose: Transform4) {
+        let index = self.keyframes.partition_point(|k| k.time <= time);
+        self.keyframes.insert(index, Keyframe { time, pose });
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    // The pose at `time` seconds. The keyframe track, if any, replaces `base`.
+    pub fn pose(&self, base: &Transform4, time: f32) -> Transform4 {
+        let mut pose = self.track(time).unwrap_or(*base);
+
+        for (angle, spin) in pose.angles.iter_mut().zip(self.spin) {
+            *angle += spin * time;
+        }
+        pose.translation += self.velocity * time;
+
+        pose
+    }
+
+    fn track(&self, time: f32) -> Option<Transform4> {
+        let first = self.keyframes.first()?;
+        let duration = self.duration();
+        if duration <= first.time {
+            return Some(first.pose);
+        }
+
+        let time = first.time + (time - first.time).rem_euclid(duration - first.time);
+        let next = self.keyframes.partition_point(|k| k.time <= time).min(self.keyframes.len() - 1);
+        let (a, b) = (&self.keyframes[next.max(1) - 1], &self.keyframes[next]);
+
+        let t = if b.time > a.time { (time - a.time) / (b.time - a.time) } else { 1.0 };
+        let mut pose = a.pose;
+        pose.translation = a.pose.translation.lerp(&b.pose.translation, t);
+        for (angle, target) in pose.angles.iter_mut().zip(b.pose.angles) {
+            *angle += (target - *angle) * t;
+        }
+
+        Some(pose)
+    }
+}
@@ -0,0 +1,157 @@
+use super::{Entity, Topology, Vertex};
+use super::color::hsv_to_rgb;
+
+use std::f32::consts::PI;
+extern crate nalgebra as na;
+use na::{Complex, ComplexField};
+
+// Points farther than this from the origin are left out, e.g. around the pole of 1/z.
+const MAX_COORDINATE: f32 = 8.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ComplexPreset {
+    Square,
+    Exp,
+    Sqrt,
+    Reciprocal,
+}
+
+impl ComplexPreset {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ComplexPreset::Square => "z²",
+            ComplexPreset::Exp => "exp(z)",
+            ComplexPreset::Sqrt => "sqrt(z)",
+            ComplexPreset::Reciprocal => "1/z",
+        }
+    }
+}
+
+impl Entity {
+    // The graph of f: C -> C as the 2-surface (Re z, Im z, Re f(z), Im f(z)),
+    // sampled on a resolution[0] x resolution[1] grid over re x im.
+    pub fn complex_graph<F>(
+        f: F,
+        re: (f32, f32),
+        im: (f32, f32),
+        resolution: [usize; 2],
+    ) -> Self
+    where
+        F: Fn(Complex<f32>) -> Complex<f32>,
+    {
+        Self::complex_surface(|z| (z, f(z)), re, im, resolution)
+    }
+
+    // A surface given by a parameter t -> (z(t), f(t)) over re x im.
+    // Multivalued functions are drawn on their Riemann surface this way,
+    // e.g. sqrt with t -> (t², t).
+    pub fn complex_surface<P>(
+        param: P,
+        re: (f32, f32),
+        im: (f32, f32),
+        resolution: [usize; 2],
+    ) -> Self
+    where
+        P: Fn(Complex<f32>) -> (Complex<f32>, Complex<f32>),
+    {
+        let [n, m] = resolution.map(|r| r.max(1));
+
+        let mut vertices = Vec::new();
+        let mut grid = vec![None; (n + 1) * (m + 1)];
+
+        for j in 0..=m {
+            for i in 0..=n {
+                let t = Complex::new(
+                    re.0 + (re.1 - re.0) * i as f32 / n as f32,
+                    im.0 + (im.1 - im.0) * j as f32 / m as f32,
+                );
+                let (z, w) = param(t);
+                let pos = [z.re, z.im, w.re, w.im];
+
+                if pos.iter().all(|x| x.is_finite() && x.abs() <= MAX_COORDINATE) {
+                    grid[j * (n + 1) + i] = Some(vertices.len() as u32);
+                    vertices.push(Vertex {
+                        pos,
+                        color: domain_color(w),
+                    });
+                }
+            }
+        }
+
+        let mut faces = Vec::new();
+
+        for j in 0..m {
+            for i in 0..n {
+                let corners = [
+                    grid[j * (n + 1) + i],
+                    grid[j * (n + 1) + i + 1],
+                    grid[(j + 1) * (n + 1) + i + 1],
+                    grid[(j + 1) * (n + 1) + i],
+                ];
+
+                if let [Some(a), Some(b), Some(c), Some(d)] = corners {
+                    faces.push(vec![a, b, c, d]);
+                }
+            }
+        }
+
+        Entity::new(vertices, Topology::from_faces(faces, Vec::new()))
+    }
+
+    pub fn complex_preset(preset: ComplexPreset, resolution: usize) -> Self {
+        let resolution = [resolution, resolution];
+
+        match preset {
+            ComplexPreset::Square => {
+                Self::complex_graph(|z| z * z, (-1.5, 1.5), (-1.5, 1.5), resolution)
+            }
+            ComplexPreset::Exp => {
+                Self::complex_graph(|z| z.exp(), (-1.5, 1.5), (-PI, PI), resolution)
+            }
+            // both branches at once: t runs over the square (-1.2, 1.2)² and z = t²
+            ComplexPreset::Sqrt => {
+                Self::complex_surface(|t| (t * t, t), (-1.2, 1.2), (-1.2, 1.2), resolution)
+            }
+            ComplexPreset::Reciprocal => {
+                Self::complex_graph(|z| z.inv(), (-2.0, 2.0), (-2.0, 2.0), resolution)
+            }
+        }
+    }
+}
+
+// Domain coloring: the hue follows arg f, the brightness repeats with every doubling of |f|.
+fn domain_color(w: Complex<f32>) -> [f32; 3] {
+    let hue = (w.argument() / (2.0 * PI)).rem_euclid(1.0);
+    let modulus = w.modulus().max(f32::MIN_POSITIVE);
+    let value = 0.6 + 0.4 * modulus.log2().rem_euclid(1.0);
+
+    hsv_to_rgb(hue, 1.0, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graph_of_the_square() {
+        let entity = Entity::complex_graph(|z| z * z, (-1.0, 1.0), (-1.0, 1.0), [4, 4]);
+        let vertices = entity.vertices();
+
+        assert_eq!(vertices.len(), 25);
+        assert_eq!(entity.topology().faces.len(), 16);
+        for vertex in vertices {
+            let [x, y, re, im] = vertex.pos;
+            assert!((re - (x * x - y * y)).abs() < 1e-6);
+            assert!((im - 2.0 * x * y).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn leaves_out_the_pole() {
+        // the grid hits 0, where 1/z is infinite
+        let entity = Entity::complex_graph(|z| z.inv(), (-1.0, 1.0), (-1.0, 1.0), [4, 4]);
+
+        assert_eq!(entity.vertices().len(), 24);
+        assert_eq!(entity.topology().faces.len(), 12);
+    }
+}
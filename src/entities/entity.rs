@@ -1,48 +1,64 @@
-use super::Vertex;
+use super::{Animation, Topology, Vertex};
+use crate::core::transform::Transform4;
 
 // use std::time::Instant;
 // use chrono::
 extern crate nalgebra as na;
 
+#[derive(Clone)]
 pub struct Entity {
     vertices: Vec<Vertex>,
-    indices: Vec<u16>,
+    indices: Vec<u32>,
+    topology: Topology,
+    names: Vec<Option<String>>,
+    pose: Transform4,
+    animation: Option<Animation>,
     transform: na::Matrix5<f32>,
 }
 
 impl Entity {
-    pub fn simplex() -> Self {
-        let vertices = vec![
-            Vertex { pos: [-0.5, -0.5, -0.5, -0.5], color: [1.0, 0.0, 0.0] },
-            Vertex { pos: [ 0.5, -0.5,  0.5, -0.5], color: [0.0, 1.0, 0.0] },
-            Vertex { pos: [-0.5,  0.5,  0.5, -0.5], color: [0.0, 0.0, 1.0] },
-            Vertex { pos: [-0.5,  0.5, -0.5,  0.5], color: [1.0, 1.0, 1.0] },
-            Vertex { pos: [ 0.5, -0.5, -0.5,  0.5], color: [1.0, 0.0, 0.0] },
-        ];
-
-        let indices = vec![
-            0, 1, 2,
-            0, 1, 3,
-            0, 2, 3,
-            1, 2, 3,
-            
-            0, 1, 4,
-            0, 2, 4,
-            0, 3, 4,
-            1, 2, 4,
-            1, 3, 4,
-            2, 3, 4,
-        ];
-
+    pub fn new(vertices: Vec<Vertex>, topology: Topology) -> Self {
+        let indices = topology.line_indices();
         let transform = na::Matrix5::identity();
 
         Self {
             vertices,
             indices,
+            topology,
+            names: Vec::new(),
+            pose: Transform4::default(),
+            animation: None,
             transform,
         }
     }
 
+    pub fn simplex() -> Self {
+        // A regular 5-cell centered at the origin, with edge length sqrt(2).
+        let low = -0.5 / 5f32.sqrt();
+        let high = 2.0 / 5f32.sqrt();
+
+        let vertices = vec![
+            Vertex { pos: [ 0.5,  0.5,  0.5,  low], color: [1.0, 0.0, 0.0] },
+            Vertex { pos: [ 0.5, -0.5, -0.5,  low], color: [0.0, 1.0, 0.0] },
+            Vertex { pos: [-0.5,  0.5, -0.5,  low], color: [0.0, 0.0, 1.0] },
+            Vertex { pos: [-0.5, -0.5,  0.5,  low], color: [1.0, 1.0, 1.0] },
+            Vertex { pos: [ 0.0,  0.0,  0.0, high], color: [1.0, 0.0, 0.0] },
+        ];
+
+        // every cell is the tetrahedron opposite to one vertex
+        let topology = Topology::from_tetrahedra(&[
+            [1, 2, 3, 4],
+            [0, 2, 3, 4],
+            [0, 1, 3, 4],
+            [0, 1, 2, 4],
+            [0, 1, 2, 3],
+        ]);
+
+        let mut simplex = Self::new(vertices, topology);
+        simplex.set_vertex_names(["A", "B", "C", "D", "E"].map(|n| Some(n.to_string())).to_vec());
+        simplex
+    }
+
     pub fn hypercube() -> Self {
         // let vertices = vec![
         //     Vertex { pos: [-0.5, -0.5, -0.5, -0.5], color: [1.0, 0.0, 0.0] },
@@ -63,7 +79,7 @@ impl Entity {
         //     Vertex { pos: [ 0.5,  0.5,  0.5,  0.5], color: [1.0, 1.0, 1.0] },
         // ];
 
-        let mut vertices = vec![
+        let vertices = vec![
             Vertex { pos: [-0.5, -0.5, -0.5, -0.5], color: [1.0, 0.0, 0.0] },
             Vertex { pos: [-0.5, -0.5, -0.5,  0.5], color: [0.0, 1.0, 0.0] },
             Vertex { pos: [-0.5, -0.5,  0.5, -0.5], color: [0.0, 0.0, 1.0] },
@@ -82,178 +98,101 @@ impl Entity {
             Vertex { pos: [ 0.5,  0.5,  0.5,  0.5], color: [1.0, 1.0, 1.0] },
         ];
 
-        // for v in vertices.iter_mut() {
-        //     if v.pos[3] == 0.5 {
-        //         v.pos[0] = v.pos[0] * 0.6;
-        //         v.pos[1] = v.pos[1] * 0.6;
-        //         v.pos[2] = v.pos[2] * 0.6;
-        //         v.pos[3] = v.pos[3] * 0.6;
-        //     }
-        // }
-
-        // let indices = vec![
-        //     // z, w
-        //     // 0, 1, 2, 3
-        //     0, 1, 2,        1, 2, 3, 
-        //     // 4, 5, 6, 7
-        //     4, 5, 6,        5, 6, 7,
-        //     // 8, 9, 10, 11
-        //     8, 9, 10,       9, 10, 11,
-        //     // 12, 13, 14, 15
-        //     12, 13, 14,     13, 14, 15,
-            
-        //     // y, w
-        //     // 0, 1, 4, 5
-        //     0, 1, 4,        1, 4, 5,
-        //     // 2, 3, 6, 7
-        //     2, 3, 6,        3, 6, 7,
-        //     // 8, 9, 12, 13
-        //     8, 9, 12,       9, 12, 13,
-        //     // 10, 11, 14, 15
-        //     10, 11, 14,     11, 14, 15,
-
-        //     // x, w
-        //     // 0, 1, 8, 9
-        //     0, 1, 8,        1, 8, 9,
-        //     // 2, 3, 10, 11
-        //     2, 3, 10,       3, 10, 11,
-        //     // 4, 5, 12, 13
-        //     4, 5, 12,       5, 12, 13,
-        //     // 6, 7, 14, 15
-        //     6, 7, 14,       7, 14, 15,
-
-        //     // y, z
-        //     // 0, 2, 4, 6
-        //     0, 2, 4,        2, 4, 6,
-        //     // 1, 3, 5, 7
-        //     1, 3, 5,        3, 5, 7,
-        //     // 8, 10, 12, 14
-        //     8, 10, 12,      10, 12, 14,
-        //     // 9, 11, 13, 15
-        //     9, 11, 13,      11, 13, 15,
-
-        //     // x, z
-        //     // 0, 2, 8, 10
-        //     0, 2, 8,        2, 8, 10,
-        //     // 1, 3, 9, 11
-        //     1, 3, 9,        3, 9, 11,
-        //     // 4, 6, 12, 14
-        //     4, 6, 12,       6, 12, 14,
-        //     // 5, 7, 13, 15
-        //     5, 7, 13,       7, 13, 15,
-
-        //     // x, y
-        //     // 0, 4, 8, 12
-        //     0, 4, 8,        4, 8, 12,
-        //     // 1, 5, 9, 13
-        //     1, 5, 9,        5, 9, 13,
-        //     // 2, 6, 10, 14
-        //     2, 6, 10,       6, 10, 14,
-        //     // 3, 7, 11, 15
-        //     3, 7, 11,       7, 11, 15,
-        // ];
+        // The vertex index bits are (x, y, z, w) from the highest to the lowest.
+        // A square face spans two axes, with the other two fixed at -0.5 or 0.5.
+        let bit = |axis: usize| 1u32 << (3 - axis);
 
-        // let indices = vec![
-        //     // z, w
-        //     // 0, 1, 2, 3
-        //     0, 1, 0,        1, 2, 1,        2, 3, 2,        3, 0, 3,
-        //     // 4, 5, 6, 7
-        //     4, 5, 4,        5, 6, 5,        6, 7, 6,        7, 4, 7,
-        //     // 8, 9, 10, 11
-        //     8, 9, 8,        9, 10, 9,       10, 11, 10,     11, 8, 11,
-        //     // 12, 13, 14, 15
-        //     12, 13, 12,     13, 14, 13,     14, 15, 14,     15, 12, 15,
-            
-        //     // y, w
-        //     // 0, 1, 4, 5
-        //     0, 1, 0,        1, 4, 1,        4, 5, 4,        5, 0, 5,
-        //     // 2, 3, 6, 7
-        //     2, 3, 2,        3, 6, 3,        6, 7, 6,        7, 2, 7,
-        //     // 8, 9, 12, 13
-        //     8, 9, 8,        9, 12, 9,       12, 13, 12,     13, 8, 13,
-        //     // 10, 11, 14, 15
-        //     10, 11, 10,     11, 14, 11,     14, 15, 14,     15, 10, 15,
-
-        //     // x, w
-        //     // 0, 1, 8, 9
-        //     0, 1, 8,        1, 8, 9,
-        //     // 2, 3, 10, 11
-        //     2, 3, 10,       3, 10, 11,
-        //     // 4, 5, 12, 13
-        //     4, 5, 12,       5, 12, 13,
-        //     // 6, 7, 14, 15
-        //     6, 7, 14,       7, 14, 15,
-
-        //     // y, z
-        //     // 0, 2, 4, 6
-        //     0, 2, 4,        2, 4, 6,
-        //     // 1, 3, 5, 7
-        //     1, 3, 5,        3, 5, 7,
-        //     // 8, 10, 12, 14
-        //     8, 10, 12,      10, 12, 14,
-        //     // 9, 11, 13, 15
-        //     9, 11, 13,      11, 13, 15,
-
-        //     // x, z
-        //     // 0, 2, 8, 10
-        //     0, 2, 8,        2, 8, 10,
-        //     // 1, 3, 9, 11
-        //     1, 3, 9,        3, 9, 11,
-        //     // 4, 6, 12, 14
-        //     4, 6, 12,       6, 12, 14,
-        //     // 5, 7, 13, 15
-        //     5, 7, 13,       7, 13, 15,
-
-        //     // x, y
-        //     // 0, 4, 8, 12
-        //     0, 4, 8,        4, 8, 12,
-        //     // 1, 5, 9, 13
-        //     1, 5, 9,        5, 9, 13,
-        //     // 2, 6, 10, 14
-        //     2, 6, 10,       6, 10, 14,
-        //     // 3, 7, 11, 15
-        //     3, 7, 11,       7, 11, 15,
-        // ];
+        let mut faces = Vec::new();
+        let mut face_planes = Vec::new();
 
-        // framework
-        let indices = vec!(
-            // 0, 1, 0,
-            // 0, 2, 0
-            0, 1, 0,    0, 2, 0,    0, 4, 0,    0, 8, 0,
-            1, 3, 1,    1, 5, 1,    1, 9, 1,
-            2, 3, 2,    2, 6, 2,    2, 10, 2,
-            3, 7, 3,    3, 11, 3,
-            4, 5, 4,    4, 6, 4,    4, 12, 4,
-            5, 7, 5,    5, 13, 5,
-            6, 7, 6,    6, 14, 6,
-            7, 15, 7,
-            8, 9, 8,    8, 10, 8,   8, 12, 8,
-            9, 11, 9,   9, 13, 9,
-            10, 11, 10,     10, 14, 10,
-            11, 15, 11,
-            12, 13, 12,     12, 14, 12,
-            13, 15, 13,
-            14, 15, 14,
-        );
+        for a in 0..4 {
+            for b in (a + 1)..4 {
+                let fixed: Vec<usize> = (0..4).filter(|&k| k != a && k != b).collect();
 
-        let transform = na::Matrix5::identity();
+                for signs in 0..4u32 {
+                    let base = (signs & 1) * bit(fixed[0]) + (signs >> 1) * bit(fixed[1]);
 
-        Self {
-            vertices,
-            indices,
-            transform,
+                    faces.push(vec![base, base + bit(a), base + bit(a) + bit(b), base + bit(b)]);
+                    face_planes.push((a, b, base));
+                }
+            }
         }
+
+        // A cubic cell fixes one axis, and holds the faces that fix it to the same value.
+        let mut cells = Vec::new();
+
+        for axis in 0..4 {
+            for value in [0, bit(axis)] {
+                let cell = face_planes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &(a, b, base))| a != axis && b != axis && base & bit(axis) == value)
+                    .map(|(index, _)| index)
+                    .collect();
+
+                cells.push(cell);
+            }
+        }
+
+        // name the vertices by their bits, e.g. "0110"
+        let names = (0..16).map(|i| Some(format!("{:04b}", i))).collect();
+
+        let mut hypercube = Self::new(vertices, Topology::from_faces(faces, cells));
+        hypercube.set_vertex_names(names);
+        hypercube
     }
 
     pub fn vertices(&self) -> Vec<Vertex> {
         self.vertices.clone()
     }
 
-    pub fn indices(&self) -> Vec<u16> {
+    pub fn indices(&self) -> Vec<u32> {
         self.indices.clone()
     }
 
+    pub fn topology(&self) -> &Topology {
+        &self.topology
+    }
+
+    // Without vertices or edges there is nothing to draw, and no buffers can be made for it.
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty() || self.indices.is_empty()
+    }
+
+    pub fn vertex_name(&self, index: usize) -> Option<String> {
+        self.names.get(index).cloned().flatten()
+    }
+
+    pub fn set_vertex_names(&mut self, names: Vec<Option<String>>) {
+        self.names = names;
+    }
+
     pub fn transform(&self) -> na::Matrix5<f32> {
         self.transform.clone()
     }
-}
\ No newline at end of file
+
+    pub fn pose(&self) -> Transform4 {
+        self.pose
+    }
+
+    pub fn set_pose(&mut self, pose: Transform4) {
+        self.pose = pose;
+        self.transform = pose.matrix();
+    }
+
+    pub fn animation(&self) -> Option<&Animation> {
+        self.animation.as_ref()
+    }
+
+    pub fn set_animation(&mut self, animation: Option<Animation>) {
+        self.animation = animation;
+        self.transform = self.pose.matrix();
+    }
+
+    // Move the entity to where its animation is at `time` seconds.
+    pub fn animate(&mut self, time: f32) {
+        if let Some(animation) = self.animation.as_ref() {
+            self.transform = animation.pose(&self.pose, time).matrix();
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use super::{Entity, Topology, Vertex};
+use super::color::gradient;
+use crate::core::math::cross4;
+
+use std::collections::HashMap;
+extern crate nalgebra as na;
+
+// Tolerance relative to the size of the point set.
+const EPSILON: f32 = 1e-5;
+// Tolerance for the unit normals of the facets at a vertex to span 4D.
+const NORMAL_EPSILON: f32 = 1e-4;
+
+struct Facet {
+    vertices: [usize; 4],
+    normal: na::Vector4<f32>,
+    offset: f32,
+}
+
+impl Facet {
+    // The normal points away from `interior`.
+    fn new(vertices: [usize; 4], points: &[na::Point4<f32>], interior: &na::Point4<f32>) -> Self {
+        let [a, b, c, d] = vertices.map(|i| points[i]);
+        let mut normal = cross4(&(b - a), &(c - a), &(d - a)).normalize();
+
+        if normal.dot(&(interior - a)) > 0.0 {
+            normal = -normal;
+        }
+
+        let offset = normal.dot(&a.coords);
+
+        Self {
+            vertices,
+            normal,
+            offset,
+        }
+    }
+
+    fn distance(&self, p: &na::Point4<f32>) -> f32 {
+        self.normal.dot(&p.coords) - self.offset
+    }
+
+    fn ridges(&self) -> [[usize; 3]; 4] {
+        let v = self.vertices;
+        [
+            [v[1], v[2], v[3]],
+            [v[0], v[2], v[3]],
+            [v[0], v[1], v[3]],
+            [v[0], v[1], v[2]],
+        ]
+    }
+}
+
+impl Entity {
+    // The convex hull of a 4D point set, with tetrahedral cells.
+    //
+    // Points with non-finite coordinates are skipped, coincident points are merged
+    // and only the corners of the hull become vertices,
+    // points on its boundary (e.g. the centers of the cubes of a tesseract) are dropped.
+    // Returns `None` when the points don't span 4D space.
+    pub fn convex_hull(points: &[na::Point4<f32>]) -> Option<Self> {
+        let points: Vec<na::Point4<f32>> = points
+            .iter()
+            .filter(|p| p.iter().all(|x| x.is_finite()))
+            .cloned()
+            .collect();
+
+        let scale = points
+            .iter()
+            .map(|p| p.coords.amax())
+            .fold(0.0, f32::max)
+            .max(1.0);
+        let epsilon = EPSILON * scale;
+
+        let mut unique: Vec<na::Point4<f32>> = Vec::with_capacity(points.len());
+        for p in points.iter() {
+            if unique.iter().all(|q| (p - q).norm() > epsilon) {
+                unique.push(*p);
+            }
+        }
+        let points = unique;
+
+        let simplex = initial_simplex(&points, epsilon)?;
+        let interior = simplex
+            .iter()
+            .fold(na::Point4::origin(), |c, &i| c + points[i].coords / 5.0);
+
+        let mut facets: Vec<Facet> = (0..5)
+            .map(|skip| {
+                let mut v = [0; 4];
+                for (slot, i) in (0..5).filter(|&i| i != skip).enumerate() {
+                    v[slot] = simplex[i];
+                }
+                Facet::new(v, &points, &interior)
+            })
+            .collect();
+
+        for (index, p) in points.iter().enumerate() {
+            if simplex.contains(&index) {
+                continue;
+            }
+
+            let (visible, hidden): (Vec<Facet>, Vec<Facet>) = facets
+                .into_iter()
+                .partition(|facet| facet.distance(p) > epsilon);
+            facets = hidden;
+
+            // the ridges on the boundary of the visible region appear exactly once
+            let mut ridge_count = HashMap::new();
+            for facet in visible.iter() {
+                for ridge in facet.ridges() {
+                    let mut key = ridge;
+                    key.sort_unstable();
+                    ridge_count.entry(key).or_insert((ridge, 0)).1 += 1;
+                }
+            }
+
+            for (ridge, count) in ridge_count.into_values() {
+                if count == 1 {
+                    let v = [ridge[0], ridge[1], ridge[2], index];
+                    facets.push(Facet::new(v, &points, &interior));
+                }
+            }
+        }
+
+        // A boundary point that was added before the facets around it existed stays a vertex.
+        // At a corner the normals of the facets span 4D, so the hull is built again from those.
+        let mut normals: HashMap<usize, Vec<na::Vector4<f32>>> = HashMap::new();
+        for facet in facets.iter() {
+            for &v in facet.vertices.iter() {
+                normals.entry(v).or_default().push(facet.normal);
+            }
+        }
+
+        let corners: Vec<na::Point4<f32>> = normals
+            .iter()
+            .filter(|(_, normals)| spans4(normals))
+            .map(|(&v, _)| points[v])
+            .collect();
+        if corners.len() < normals.len() {
+            return Self::convex_hull(&corners);
+        }
+
+        let mut vertex_map = HashMap::new();
+        let mut vertices = Vec::new();
+
+        let (w_min, w_max) = points
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p[3]), hi.max(p[3])));
+
+        let tetrahedra: Vec<[u32; 4]> = facets
+            .iter()
+            .map(|facet| {
+                facet.vertices.map(|i| {
+                    *vertex_map.entry(i).or_insert_with(|| {
+                        let p = points[i];
+                        vertices.push(Vertex {
+                            pos: [p[0], p[1], p[2], p[3]],
+                            color: gradient((p[3] - w_min) / (w_max - w_min)),
+                        });
+                        vertices.len() as u32 - 1
+                    })
+                })
+            })
+            .collect();
+
+        Some(Entity::new(vertices, Topology::from_tetrahedra(&tetrahedra)))
+    }
+}
+
+// Five affinely independent points, each as far as possible from the span of the previous ones.
+fn initial_simplex(points: &[na::Point4<f32>], epsilon: f32) -> Option<[usize; 5]> {
+    let origin = *points.first()?;
+    let mut simplex = [0; 5];
+    let mut basis: Vec<na::Vector4<f32>> = Vec::with_capacity(4);
+
+    let residual = |p: &na::Point4<f32>, basis: &[na::Vector4<f32>]| {
+        basis
+            .iter()
+            .fold(p - origin, |r, b| r - b * b.dot(&r))
+    };
+
+    for slot in simplex.iter_mut().skip(1) {
+        let (index, r) = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, residual(p, &basis)))
+            .max_by(|a, b| a.1.norm().total_cmp(&b.1.norm()))?;
+
+        if r.norm() <= epsilon {
+            return None;
+        }
+
+        *slot = index;
+        basis.push(r.normalize());
+    }
+
+    Some(simplex)
+}
+
+fn spans4(normals: &[na::Vector4<f32>]) -> bool {
+    let mut basis: Vec<na::Vector4<f32>> = Vec::with_capacity(4);
+
+    for n in normals {
+        let r = basis.iter().fold(*n, |r, b| r - b * b.dot(&r));
+        if r.norm() > NORMAL_EPSILON {
+            basis.push(r.normalize());
+            if basis.len() == 4 {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(p: [f32; 4]) -> na::Point4<f32> {
+        na::Point4::from(p)
+    }
+
+    fn tesseract() -> Vec<na::Point4<f32>> {
+        (0..16)
+            .map(|i| point([0, 1, 2, 3].map(|axis| if i & (1 << axis) == 0 { -1.0 } else { 1.0 })))
+            .collect()
+    }
+
+    #[test]
+    fn pentachoron() {
+        let points = [
+            [0.0, 0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]
+        .map(point);
+        let report = Entity::convex_hull(&points).unwrap().analyze();
+
+        assert_eq!([report.vertices, report.edges, report.faces, report.cells], [5, 10, 10, 5]);
+        assert_eq!(report.convex, Some(true));
+    }
+
+    #[test]
+    fn keeps_only_the_corners() {
+        let mut points = tesseract();
+        // the center, the centers of the cubes and the corners once more
+        points.push(point([0.0; 4]));
+        for axis in 0..4 {
+            for sign in [-1.0, 1.0] {
+                let mut center = [0.0; 4];
+                center[axis] = sign;
+                points.push(point(center));
+            }
+        }
+        points.extend(tesseract());
+
+        let entity = Entity::convex_hull(&points).unwrap();
+        let report = entity.analyze();
+
+        assert_eq!(report.vertices, 16);
+        assert!(entity.vertices().iter().all(|v| v.pos.iter().all(|x| x.abs() == 1.0)));
+        assert_eq!(report.euler, 0);
+        assert!(report.open_faces.is_empty());
+        assert_eq!(report.convex, Some(true));
+    }
+
+    #[test]
+    fn rejects_flat_points() {
+        let coplanar: Vec<_> = tesseract().into_iter().map(|p| point([p[0], p[1], p[2], 0.0])).collect();
+        let collinear: Vec<_> = (0..8).map(|i| point([i as f32, 2.0 * i as f32, 0.0, 1.0])).collect();
+
+        assert!(Entity::convex_hull(&coplanar).is_none());
+        assert!(Entity::convex_hull(&collinear).is_none());
+        assert!(Entity::convex_hull(&[]).is_none());
+    }
+
+    #[test]
+    fn skips_points_that_are_not_finite() {
+        let mut points = vec![point([f32::NAN, 0.0, 0.0, 0.0]), point([0.0, f32::INFINITY, 0.0, 0.0])];
+        points.extend(tesseract());
+        points.push(point([0.0, 0.0, f32::NAN, 0.0]));
+
+        assert_eq!(Entity::convex_hull(&points).unwrap().analyze().vertices, 16);
+        assert!(Entity::convex_hull(&[point([f32::NAN; 4])]).is_none());
+    }
+}
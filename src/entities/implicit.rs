@@ -0,0 +1,210 @@
+use super::{Entity, Topology, Vertex};
+use super::color::gradient;
+
+use std::collections::HashMap;
+extern crate nalgebra as na;
+
+// Vertices of a triangular prism, bottom (0, 1, 2) and top (3, 4, 5),
+// relabeled so that the given vertex becomes vertex 0.
+const PRISM_ROTATIONS: [[usize; 6]; 6] = [
+    [0, 1, 2, 3, 4, 5],
+    [1, 2, 0, 4, 5, 3],
+    [2, 0, 1, 5, 3, 4],
+    [3, 5, 4, 0, 2, 1],
+    [4, 3, 5, 1, 0, 2],
+    [5, 4, 3, 2, 1, 0],
+];
+
+impl Entity {
+    // Extract the zero set of `field` inside the box [min, max] as a tetrahedral mesh.
+    //
+    // The box is divided into resolution[0] x ... x resolution[3] hypercubes and every hypercube
+    // into 24 pentatopes (4-simplices, like `Entity::simplex`) along its main diagonal.
+    // The zero set inside a pentatope is either a tetrahedron or a triangular prism,
+    // which is split into three tetrahedra.
+    pub fn implicit<F>(
+        field: F,
+        min: na::Point4<f32>,
+        max: na::Point4<f32>,
+        resolution: [usize; 4],
+    ) -> Self
+    where
+        F: Fn(&na::Point4<f32>) -> f32,
+    {
+        let resolution = resolution.map(|r| r.max(1));
+        let counts = resolution.map(|r| r + 1);
+        let step = na::Vector4::from_fn(|i, _| (max[i] - min[i]) / resolution[i] as f32);
+
+        let grid_index = |p: [usize; 4]| {
+            ((p[3] * counts[2] + p[2]) * counts[1] + p[1]) * counts[0] + p[0]
+        };
+
+        let grid_point = |id: usize| {
+            let mut rest = id;
+            let mut p = min;
+            for i in 0..4 {
+                p[i] += (rest % counts[i]) as f32 * step[i];
+                rest /= counts[i];
+            }
+            p
+        };
+
+        let values: Vec<f32> = (0..counts.iter().product())
+            .map(|id| field(&grid_point(id)))
+            .collect();
+
+        let mut vertices = Vec::new();
+        let mut vertex_map = HashMap::new();
+        let mut tetrahedra = Vec::new();
+
+        // the point where the field crosses zero on the edge from `a` (inside) to `b` (outside)
+        let mut edge_vertex = |a: usize, b: usize| -> u32 {
+            let (va, vb) = (values[a], values[b]);
+            let key = if vb == 0.0 { (b, b) } else { (a.min(b), a.max(b)) };
+
+            *vertex_map.entry(key).or_insert_with(|| {
+                let t = va / (va - vb);
+                let pa = grid_point(a);
+                let pb = grid_point(b);
+                let pos = pa + (pb - pa) * t;
+
+                vertices.push(Vertex {
+                    pos: [pos[0], pos[1], pos[2], pos[3]],
+                    // blue at the lowest w of the bounds, red at the highest
+                    color: gradient((pos[3] - min[3]) / (max[3] - min[3]).max(f32::EPSILON)),
+                });
+
+                vertices.len() as u32 - 1
+            })
+        };
+
+        for cube in 0..resolution.iter().product() {
+            let mut rest = cube;
+            let corner = [0, 1, 2, 3].map(|i| {
+                let c = rest % resolution[i];
+                rest /= resolution[i];
+                c
+            });
+
+            for permutation in permutations4() {
+                let mut p = corner;
+                let mut simplex = [grid_index(p); 5];
+                for (k, &axis) in permutation.iter().enumerate() {
+                    p[axis] += 1;
+                    simplex[k + 1] = grid_index(p);
+                }
+
+                let (inside, outside): (Vec<usize>, Vec<usize>) = simplex
+                    .into_iter()
+                    .partition(|&id| values[id] < 0.0);
+
+                match (inside.len(), outside.len()) {
+                    (1, 4) => {
+                        let t = [0, 1, 2, 3].map(|i| edge_vertex(inside[0], outside[i]));
+                        tetrahedra.push(t);
+                    }
+                    (4, 1) => {
+                        let t = [0, 1, 2, 3].map(|i| edge_vertex(inside[i], outside[0]));
+                        tetrahedra.push(t);
+                    }
+                    (2, 3) => {
+                        let prism = [0, 1, 2, 3, 4, 5]
+                            .map(|i| edge_vertex(inside[i / 3], outside[i % 3]));
+                        tetrahedra.extend(split_prism(prism));
+                    }
+                    (3, 2) => {
+                        let prism = [0, 1, 2, 3, 4, 5]
+                            .map(|i| edge_vertex(inside[i % 3], outside[i / 3]));
+                        tetrahedra.extend(split_prism(prism));
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        // drop the tetrahedra collapsed by field values that are exactly zero
+        tetrahedra.retain(|t| {
+            (0..4).all(|i| (i + 1..4).all(|j| t[i] != t[j]))
+        });
+
+        Entity::new(vertices, Topology::from_tetrahedra(&tetrahedra))
+    }
+}
+
+fn permutations4() -> Vec<[usize; 4]> {
+    let mut permutations = Vec::with_capacity(24);
+
+    for a in 0..4 {
+        for b in (0..4).filter(|&b| b != a) {
+            for c in (0..4).filter(|&c| c != a && c != b) {
+                let d = 6 - a - b - c;
+                permutations.push([a, b, c, d]);
+            }
+        }
+    }
+
+    permutations
+}
+
+// Split a prism into three tetrahedra. Every quadrilateral side is cut through
+// its smallest vertex index, so neighbouring pentatopes agree on the diagonal.
+fn split_prism(prism: [u32; 6]) -> [[u32; 4]; 3] {
+    let smallest = (0..6).min_by_key(|&i| prism[i]).unwrap();
+    let v = PRISM_ROTATIONS[smallest].map(|i| prism[i]);
+
+    if v[1].min(v[5]) < v[2].min(v[4]) {
+        [
+            [v[0], v[1], v[2], v[5]],
+            [v[0], v[1], v[5], v[4]],
+            [v[0], v[4], v[5], v[3]],
+        ]
+    } else {
+        [
+            [v[0], v[1], v[2], v[4]],
+            [v[0], v[4], v[2], v[5]],
+            [v[0], v[4], v[5], v[3]],
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glome(bounds: f32) -> Entity {
+        Entity::implicit(
+            |p| p.coords.norm_squared() - 1.0,
+            na::Point4::from([-bounds; 4]),
+            na::Point4::from([bounds; 4]),
+            [8; 4],
+        )
+    }
+
+    #[test]
+    fn extracts_the_glome() {
+        let entity = glome(1.2);
+        let topology = entity.topology();
+
+        assert!(!entity.is_empty());
+        // the field is interpolated linearly along the edges of the pentatopes
+        for vertex in entity.vertices() {
+            let radius = na::Vector4::from(vertex.pos).norm();
+            assert!((radius - 1.0).abs() < 0.05, "a vertex at radius {}", radius);
+        }
+
+        // a closed 3-manifold, without running the slow convexity check of `analyze`
+        let mut face_cells = vec![0; topology.faces.len()];
+        for &face in topology.cells.iter().flatten() {
+            face_cells[face] += 1;
+        }
+        assert!(face_cells.iter().all(|&count| count == 2));
+
+        let euler = entity.vertices().len() + topology.faces.len() - topology.edges.len() - topology.cells.len();
+        assert_eq!(euler, 0);
+    }
+
+    #[test]
+    fn is_empty_when_the_bounds_miss_the_zero_set() {
+        assert!(glome(0.5).is_empty());
+    }
+}
@@ -1,6 +1,32 @@
 mod entity;
 pub use entity::Entity;
 
+mod color;
+
+mod topology;
+pub use topology::Topology;
+
+mod implicit;
+
+mod preset;
+pub use preset::Preset;
+
+mod complex;
+pub use complex::ComplexPreset;
+
+mod hull;
+
+mod unfold;
+pub use unfold::Unfolding;
+
+mod off;
+
+mod analysis;
+pub use analysis::Check;
+
+mod animation;
+pub use animation::Animation;
+
 // mod hypercube;
 // pub use hypercube::Hypercube;
 
@@ -0,0 +1,233 @@
+use super::{Entity, Topology, Vertex};
+use super::color::gradient;
+
+use std::path::Path;
+use std::str::FromStr;
+extern crate nalgebra as na;
+
+impl Entity {
+    // A polytope in the 4OFF format, or the convex hull of a point list.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+        let off = matches!(content_lines(&text).next(), Some((_, "4OFF")));
+
+        if off {
+            Self::from_off(&text)
+        } else {
+            Self::from_points(&text)
+        }
+    }
+
+    // Read a polytope in the 4OFF format of Stella4D:
+    //
+    //   4OFF
+    //   vertices faces edges cells
+    //   x y z w          for every vertex
+    //   n v1 ... vn      for every face, its vertex loop
+    //   n f1 ... fn      for every cell, its faces
+    //
+    // `#` starts a comment. Colors after a face or a cell are ignored,
+    // the vertices are colored by their w coordinate and the edges follow from the faces.
+    pub fn from_off(text: &str) -> Result<Self, String> {
+        let mut lines = content_lines(text);
+
+        match lines.next() {
+            Some((_, "4OFF")) => (),
+            _ => return Err("the file does not start with 4OFF".to_string()),
+        }
+
+        let counts: Vec<usize> = numbers(lines.next(), 4)?;
+        let (vertex_count, face_count, cell_count) = (counts[0], counts[1], counts[3]);
+
+        // the counts are only trusted as far as the file has lines for them
+        let mut positions = Vec::new();
+        for _ in 0..vertex_count {
+            let coordinates: Vec<f32> = numbers(lines.next(), 4)?;
+            positions.push([coordinates[0], coordinates[1], coordinates[2], coordinates[3]]);
+        }
+
+        let mut faces = Vec::new();
+        for _ in 0..face_count {
+            faces.push(list(lines.next(), vertex_count, "vertex")?);
+        }
+
+        let mut cells = Vec::new();
+        for _ in 0..cell_count {
+            let cell = list(lines.next(), face_count, "face")?;
+            cells.push(cell.into_iter().map(|face| face as usize).collect());
+        }
+
+        let (low, high) = positions
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(low, high), p| (low.min(p[3]), high.max(p[3])));
+        let range = (high - low).max(f32::EPSILON);
+
+        let vertices = positions
+            .into_iter()
+            .map(|pos| Vertex { pos, color: gradient((pos[3] - low) / range) })
+            .collect();
+
+        let entity = Self::new(vertices, Topology::from_faces(faces, cells));
+
+        // nothing could be drawn of it
+        if entity.vertices().is_empty() {
+            return Err("the polytope has no vertices".to_string());
+        }
+        if entity.is_empty() {
+            return Err("the polytope has no edges".to_string());
+        }
+
+        Ok(entity)
+    }
+
+    // The convex hull of the points in the input format of qhull:
+    //
+    //   4                the dimension
+    //   n                the number of points
+    //   x y z w          for every point
+    //
+    // `#` starts a comment.
+    pub fn from_points(text: &str) -> Result<Self, String> {
+        let mut lines = content_lines(text);
+
+        match lines.next() {
+            Some((_, "4")) => (),
+            _ => return Err("the file starts with neither 4OFF nor the dimension 4 of a point list".to_string()),
+        }
+
+        let count = numbers::<usize>(lines.next(), 1)?[0];
+
+        let mut points = Vec::new();
+        for _ in 0..count {
+            let coordinates: Vec<f32> = numbers(lines.next(), 4)?;
+            points.push(na::Point4::new(coordinates[0], coordinates[1], coordinates[2], coordinates[3]));
+        }
+
+        Self::convex_hull(&points).ok_or_else(|| "the points don't span four dimensions".to_string())
+    }
+}
+
+// The numbered lines without comments and blank lines.
+fn content_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.split('#').next().unwrap_or("").trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+// At least `count` numbers; any after them are ignored.
+fn numbers<T: FromStr>(line: Option<(usize, &str)>, count: usize) -> Result<Vec<T>, String> {
+    let (number, line) = line.ok_or("the file ends early")?;
+
+    let values = line
+        .split_whitespace()
+        .map(|word| word.parse().map_err(|_| format!("line {}: {} is not a number", number, word)))
+        .take(count)
+        .collect::<Result<Vec<T>, String>>()?;
+
+    if values.len() < count {
+        return Err(format!("line {}: expected {} numbers", number, count));
+    }
+
+    Ok(values)
+}
+
+// A count followed by that many indices below `bound`.
+fn list(line: Option<(usize, &str)>, bound: usize, name: &str) -> Result<Vec<u32>, String> {
+    let number = line.map_or(0, |(number, _)| number);
+    let count = numbers::<usize>(line, 1)?[0];
+    let words = count.checked_add(1).ok_or(format!("line {}: {} is too many indices", number, count))?;
+    let indices: Vec<usize> = numbers(line, words)?;
+
+    indices[1..]
+        .iter()
+        .map(|&index| {
+            if index < bound {
+                Ok(index as u32)
+            } else {
+                Err(format!("line {}: there is no {} {}", number, name, index))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PENTACHORON: &str = "
+        4OFF
+        # a pentachoron
+        5 10 10 5
+        0 0 0 0
+        1 0 0 0
+        0 1 0 0
+        0 0 1 0
+        0 0 0 1
+        3 0 1 2
+        3 0 1 3
+        3 0 2 3
+        3 1 2 3
+        3 0 1 4
+        3 0 2 4
+        3 1 2 4
+        3 0 3 4
+        3 1 3 4
+        3 2 3 4
+        4 0 1 2 3
+        4 0 4 5 6
+        4 1 4 7 8 1.0 0.0 0.0   # a color, which is ignored
+        4 2 5 7 9
+        4 3 6 8 9
+    ";
+
+    #[test]
+    fn reads_a_polytope() {
+        let entity = Entity::from_off(PENTACHORON).unwrap();
+        let topology = entity.topology();
+
+        assert_eq!(entity.vertices().len(), 5);
+        assert_eq!(entity.vertices()[4].pos, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(topology.edges.len(), 10);
+        assert_eq!(topology.faces.len(), 10);
+        assert_eq!(topology.cells[2], vec![1, 4, 7, 8]);
+        assert_eq!(topology.cells.len(), 5);
+    }
+
+    #[test]
+    fn reports_a_truncated_file() {
+        let truncated: String = PENTACHORON.lines().take(12).collect::<Vec<_>>().join("\n");
+
+        assert_eq!(Entity::from_off(&truncated).err().unwrap(), "the file ends early");
+        assert_eq!(Entity::from_off("4OFF\n5 10 10").err().unwrap(), "line 2: expected 4 numbers");
+        assert_eq!(Entity::from_off("4OFF\n1 0 0 0\n0 0 x 0").err().unwrap(), "line 3: x is not a number");
+        assert_eq!(Entity::from_off("OFF\n").err().unwrap(), "the file does not start with 4OFF");
+    }
+
+    #[test]
+    fn rejects_indices_out_of_range() {
+        let text = "4OFF\n1 1 0 0\n0 0 0 0\n3 0 0 1";
+
+        assert_eq!(Entity::from_off(text).err().unwrap(), "line 4: there is no vertex 1");
+    }
+
+    #[test]
+    fn rejects_an_empty_polytope() {
+        assert_eq!(Entity::from_off("4OFF\n0 0 0 0").err().unwrap(), "the polytope has no vertices");
+        assert_eq!(Entity::from_off("4OFF\n1 0 0 0\n0 0 0 0").err().unwrap(), "the polytope has no edges");
+    }
+
+    #[test]
+    fn does_not_trust_the_counts() {
+        assert_eq!(Entity::from_off("4OFF\n1000000000000 0 0 0").err().unwrap(), "the file ends early");
+        assert_eq!(
+            Entity::from_off("4OFF\n1 1 0 0\n0 0 0 0\n18446744073709551615 0").err().unwrap(),
+            "line 4: 18446744073709551615 is too many indices",
+        );
+        assert_eq!(
+            Entity::from_off("4OFF\n1 1 0 0\n0 0 0 0\n1000000 0").err().unwrap(),
+            "line 4: expected 1000001 numbers",
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use super::{ComplexPreset, Entity};
+
+use std::ops::RangeInclusive;
+extern crate nalgebra as na;
+
+// The scenes built into the viewer.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Preset {
+    Tesseract,
+    Pentachoron,
+    // the 3-sphere, extracted from |p|² = 1
+    Glome,
+    // the product of two circles of radius 0.8, thickened by 0.35
+    Tiger,
+    // the graph of a complex function, colored by its argument
+    Complex(ComplexPreset),
+}
+
+impl Preset {
+    pub const ALL: [Preset; 8] = [
+        Preset::Tesseract,
+        Preset::Pentachoron,
+        Preset::Glome,
+        Preset::Tiger,
+        Preset::Complex(ComplexPreset::Square),
+        Preset::Complex(ComplexPreset::Exp),
+        Preset::Complex(ComplexPreset::Sqrt),
+        Preset::Complex(ComplexPreset::Reciprocal),
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Tesseract => "tesseract",
+            Preset::Pentachoron => "pentachoron",
+            Preset::Glome => "glome",
+            Preset::Tiger => "tiger",
+            Preset::Complex(preset) => preset.name(),
+        }
+    }
+
+    // The samples along an axis that can be chosen, for the scenes that are sampled.
+    pub fn resolutions(&self) -> Option<RangeInclusive<usize>> {
+        match self {
+            Preset::Tesseract | Preset::Pentachoron => None,
+            Preset::Glome | Preset::Tiger => Some(2..=16),
+            Preset::Complex(_) => Some(8..=64),
+        }
+    }
+
+    // Whether the scene is extracted from a box of chosen size.
+    pub fn has_bounds(&self) -> bool {
+        matches!(self, Preset::Glome | Preset::Tiger)
+    }
+
+    // `bounds` is half the edge of the box centered at the origin.
+    pub fn entity(&self, resolution: usize, bounds: f32) -> Entity {
+        let min = na::Point4::from([-bounds; 4]);
+        let max = na::Point4::from([bounds; 4]);
+
+        match self {
+            Preset::Tesseract => Entity::hypercube(),
+            Preset::Pentachoron => Entity::simplex(),
+            Preset::Glome => Entity::implicit(|p| p.coords.norm_squared() - 1.0, min, max, [resolution; 4]),
+            Preset::Tiger => Entity::implicit(
+                |p| {
+                    let a = p.x.hypot(p.y) - 0.8;
+                    let b = p.z.hypot(p.w) - 0.8;
+                    a * a + b * b - 0.35 * 0.35
+                },
+                min,
+                max,
+                [resolution; 4],
+            ),
+            Preset::Complex(preset) => Entity::complex_preset(*preset, resolution),
+        }
+    }
+}
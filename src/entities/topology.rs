@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+// The combinatorial structure of an entity:
+// edges are vertex pairs, faces are vertex loops and cells are lists of face indices.
+#[derive(Debug, Clone, Default)]
+pub struct Topology {
+    pub edges: Vec<[u32; 2]>,
+    pub faces: Vec<Vec<u32>>,
+    pub cells: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    pub fn from_faces(faces: Vec<Vec<u32>>, cells: Vec<Vec<usize>>) -> Self {
+        let mut edges = Vec::new();
+        let mut seen = HashMap::new();
+
+        for face in faces.iter() {
+            for i in 0..face.len() {
+                let a = face[i];
+                let b = face[(i + 1) % face.len()];
+                let key = (a.min(b), a.max(b));
+
+                if seen.insert(key, edges.len()).is_none() {
+                    edges.push([key.0, key.1]);
+                }
+            }
+        }
+
+        Self {
+            edges,
+            faces,
+            cells,
+        }
+    }
+
+    pub fn from_tetrahedra(tetrahedra: &[[u32; 4]]) -> Self {
+        let mut faces = Vec::new();
+        let mut cells = Vec::with_capacity(tetrahedra.len());
+        let mut face_map = HashMap::new();
+
+        for tetrahedron in tetrahedra {
+            let mut cell = Vec::with_capacity(4);
+
+            for skip in 0..4 {
+                let mut face: Vec<u32> = (0..4)
+                    .filter(|&i| i != skip)
+                    .map(|i| tetrahedron[i])
+                    .collect();
+                face.sort_unstable();
+
+                let index = *face_map.entry(face.clone()).or_insert_with(|| {
+                    faces.push(face);
+                    faces.len() - 1
+                });
+
+                cell.push(index);
+            }
+
+            cells.push(cell);
+        }
+
+        Self::from_faces(faces, cells)
+    }
+
+    pub fn cell_vertices(&self, cell: usize) -> Vec<u32> {
+        let mut vertices: Vec<u32> = self.cells[cell]
+            .iter()
+            .flat_map(|&face| self.faces[face].iter().copied())
+            .collect();
+        vertices.sort_unstable();
+        vertices.dedup();
+        vertices
+    }
+
+    pub fn cell_edges(&self, cell: usize) -> Vec<usize> {
+        let mut sides = Vec::new();
+        for &face in self.cells[cell].iter() {
+            let face = &self.faces[face];
+            for i in 0..face.len() {
+                let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                sides.push([a.min(b), a.max(b)]);
+            }
+        }
+
+        (0..self.edges.len())
+            .filter(|&e| sides.contains(&self.edges[e]))
+            .collect()
+    }
+
+    // Every edge becomes a degenerate triangle (a, b, a),
+    // so the wireframe pipeline draws it as a single line.
+    pub fn line_indices(&self) -> Vec<u32> {
+        self.edges
+            .iter()
+            .flat_map(|&[a, b]| [a, b, a])
+            .collect()
+    }
+
+    // Every face as a fan of triangles, for the solid pipeline.
+    pub fn triangle_indices(&self) -> Vec<u32> {
+        self.faces
+            .iter()
+            .flat_map(|face| (1..face.len().saturating_sub(1)).map(move |i| [face[0], face[i], face[i + 1]]))
+            .flatten()
+            .collect()
+    }
+}
@@ -0,0 +1,235 @@
+use super::{Entity, Topology};
+use crate::core::math::cross4;
+
+use std::collections::{HashMap, VecDeque};
+extern crate nalgebra as na;
+
+// The 2-face a cell turns around to lie in the hyperplane of its parent.
+struct Hinge {
+    parent: usize,
+    point: na::Vector4<f32>,
+    u: na::Vector4<f32>,
+    v: na::Vector4<f32>,
+    angle: f32,
+}
+
+impl Hinge {
+    // Rotation by `angle` in the plane (u, v), around the face through `point`.
+    fn rotation(&self, angle: f32) -> na::Matrix5<f32> {
+        let (u, v) = (self.u, self.v);
+        let r = na::Matrix4::identity()
+            + (u * u.transpose() + v * v.transpose()) * (angle.cos() - 1.0)
+            + (v * u.transpose() - u * v.transpose()) * angle.sin();
+
+        let mut m = na::Matrix5::identity();
+        m.fixed_slice_mut::<4, 4>(0, 0).copy_from(&r);
+        m.fixed_slice_mut::<4, 1>(0, 4).copy_from(&(self.point - r * self.point));
+        m
+    }
+}
+
+// Unfolds the 3-cells of a polytope into a net lying in the hyperplane of one cell.
+// The cells form a spanning tree of the cell adjacency graph, and every cell
+// turns around the 2-face it shares with its parent.
+pub struct Unfolding {
+    polytope: Entity,
+    // cells in breadth first order, so a parent always comes before its children
+    order: Vec<(usize, Option<Hinge>)>,
+}
+
+impl Unfolding {
+    pub fn new(polytope: &Entity) -> Option<Self> {
+        let topology = polytope.topology();
+        if topology.cells.is_empty() {
+            return None;
+        }
+
+        let points: Vec<na::Vector4<f32>> = polytope
+            .vertices()
+            .iter()
+            .map(|v| na::Vector4::from(v.pos))
+            .collect();
+        let center = points.iter().sum::<na::Vector4<f32>>() / points.len() as f32;
+
+        let normals: Vec<na::Vector4<f32>> = (0..topology.cells.len())
+            .map(|cell| {
+                let vertices = topology.cell_vertices(cell);
+                cell_normal(&vertices.iter().map(|&i| points[i as usize]).collect::<Vec<_>>(), &center)
+            })
+            .collect();
+
+        let mut face_cells: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (cell_index, cell) in topology.cells.iter().enumerate() {
+            for &face in cell {
+                face_cells.entry(face).or_default().push(cell_index);
+            }
+        }
+
+        // The root is the cell facing away from the default camera on the w axis,
+        // so the finished net faces the viewer.
+        let root = (0..normals.len())
+            .min_by(|&a, &b| normals[a][3].total_cmp(&normals[b][3]))
+            .unwrap();
+
+        let mut visited = vec![false; topology.cells.len()];
+        let mut order = Vec::with_capacity(topology.cells.len());
+
+        // cells not connected to the root start their own tree
+        for start in std::iter::once(root).chain(0..topology.cells.len()) {
+            if visited[start] {
+                continue;
+            }
+
+            visited[start] = true;
+            order.push((start, None));
+
+            let mut queue = VecDeque::from([start]);
+            while let Some(parent) = queue.pop_front() {
+                for &face in topology.cells[parent].iter() {
+                    for &child in face_cells[&face].iter() {
+                        if visited[child] {
+                            continue;
+                        }
+
+                        visited[child] = true;
+                        queue.push_back(child);
+
+                        let u = normals[child];
+                        let target = normals[parent];
+                        let v = target - u * target.dot(&u);
+
+                        // coplanar cells, e.g. the tetrahedra of one facet of a convex hull, stay flat
+                        let (v, angle) = match v.try_normalize(1e-6) {
+                            Some(v) => (v, target.dot(&v).atan2(target.dot(&u))),
+                            None => (na::Vector4::zeros(), 0.0),
+                        };
+
+                        let point = points[topology.faces[face][0] as usize];
+                        order.push((child, Some(Hinge { parent, point, u, v, angle })));
+                    }
+                }
+            }
+        }
+
+        Some(Self {
+            polytope: polytope.clone(),
+            order,
+        })
+    }
+
+    // The polytope unfolded by `amount`, from 0 (folded) to 1 (flat net).
+    // Every cell gets its own copy of its vertices.
+    pub fn entity(&self, amount: f32) -> Entity {
+        let topology = self.polytope.topology();
+        let vertices = self.polytope.vertices();
+
+        let mut transforms = vec![na::Matrix5::identity(); topology.cells.len()];
+        for (cell, hinge) in self.order.iter() {
+            if let Some(hinge) = hinge {
+                transforms[*cell] = transforms[hinge.parent] * hinge.rotation(hinge.angle * amount);
+            }
+        }
+
+        let mut net_vertices = Vec::new();
+        let mut net_names = Vec::new();
+        let mut net_faces = Vec::new();
+        let mut net_cells = Vec::with_capacity(topology.cells.len());
+
+        for (cell_index, cell) in topology.cells.iter().enumerate() {
+            let transform = transforms[cell_index];
+            let mut vertex_map = HashMap::new();
+
+            for &i in topology.cell_vertices(cell_index).iter() {
+                let mut vertex = vertices[i as usize];
+                let pos = transform * na::Vector5::new(vertex.pos[0], vertex.pos[1], vertex.pos[2], vertex.pos[3], 1.0);
+                vertex.pos = [pos[0], pos[1], pos[2], pos[3]];
+
+                vertex_map.insert(i, net_vertices.len() as u32);
+                net_vertices.push(vertex);
+                net_names.push(self.polytope.vertex_name(i as usize));
+            }
+
+            let mut net_cell = Vec::with_capacity(cell.len());
+            for &face in cell {
+                net_cell.push(net_faces.len());
+                net_faces.push(topology.faces[face].iter().map(|i| vertex_map[i]).collect());
+            }
+            net_cells.push(net_cell);
+        }
+
+        let mut net = Entity::new(net_vertices, Topology::from_faces(net_faces, net_cells));
+        net.set_pose(self.polytope.pose());
+        net.set_vertex_names(net_names);
+        net
+    }
+}
+
+// The normal of the hyperplane through the cell, pointing away from `center`.
+fn cell_normal(points: &[na::Vector4<f32>], center: &na::Vector4<f32>) -> na::Vector4<f32> {
+    let mut basis: Vec<na::Vector4<f32>> = Vec::with_capacity(3);
+
+    for p in points.iter().skip(1) {
+        let r = basis
+            .iter()
+            .fold(p - points[0], |r, b| r - b * b.dot(&r));
+
+        if let Some(r) = r.try_normalize(1e-6) {
+            basis.push(r);
+            if basis.len() == 3 {
+                break;
+            }
+        }
+    }
+
+    if basis.len() < 3 {
+        return na::Vector4::w();
+    }
+
+    let normal = cross4(&basis[0], &basis[1], &basis[2]).normalize();
+    if normal.dot(&(points[0] - center)) < 0.0 {
+        -normal
+    } else {
+        normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folded_is_the_polytope() {
+        let tesseract = Entity::hypercube();
+        let topology = tesseract.topology();
+        let net = Unfolding::new(&tesseract).unwrap().entity(0.0);
+
+        let positions: Vec<[f32; 4]> = (0..topology.cells.len())
+            .flat_map(|cell| topology.cell_vertices(cell))
+            .map(|i| tesseract.vertices()[i as usize].pos)
+            .collect();
+        let net_positions: Vec<[f32; 4]> = net.vertices().iter().map(|v| v.pos).collect();
+
+        assert_eq!(net_positions, positions);
+    }
+
+    #[test]
+    fn unfolded_lies_in_one_hyperplane() {
+        let unfolding = Unfolding::new(&Entity::hypercube()).unwrap();
+        let folded = unfolding.entity(0.0);
+        let net = unfolding.entity(1.0);
+        let vertices = net.vertices();
+        let w = vertices[0].pos[3];
+
+        assert_eq!(net.topology().cells.len(), 8);
+        assert!(vertices.iter().all(|v| (v.pos[3] - w).abs() < 1e-5));
+
+        // the cells turn without stretching
+        let length = |entity: &Entity, edge: &[u32; 2]| {
+            let vertices = entity.vertices();
+            (na::Vector4::from(vertices[edge[0] as usize].pos) - na::Vector4::from(vertices[edge[1] as usize].pos)).norm()
+        };
+        for edge in net.topology().edges.iter() {
+            assert!((length(&net, edge) - length(&folded, edge)).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use ash::vk;
+
+use std::path::PathBuf;
+
+// Everything that can stop the app from starting, worded for the person running it.
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("Could not open a window: {0}")]
+    Window(#[from] winit::error::OsError),
+
+    #[error("Could not load the Vulkan loader ({0}). Install the Vulkan runtime of your graphics driver, e.g. the vulkan-icd-loader or libvulkan1 package.")]
+    MissingLoader(#[from] ash::LoadingError),
+
+    #[error("No Vulkan driver is installed ({0}). Install the Vulkan driver for your GPU, or Mesa's lavapipe for software rendering.")]
+    NoDriver(vk::Result),
+
+    #[error("Validation was asked for, but the layer VK_LAYER_KHRONOS_validation is not installed. Install the Vulkan SDK or the vulkan-validation-layers package, or run without --validation and HYPER_OCULUS_VALIDATION.")]
+    MissingValidationLayer,
+
+    #[error("The Vulkan driver is missing an extension needed to draw to a window ({0}). Update your graphics driver.")]
+    MissingExtension(vk::Result),
+
+    #[error("Could not create a Vulkan surface for the window ({0}).")]
+    Surface(vk::Result),
+
+    #[error("No GPU can draw to this window, or at all for `render`. Check that a Vulkan driver for your GPU is installed.")]
+    NoSuitableDevice,
+
+    #[error("No GPU that can draw matches --device {0}. The candidates are: {1}.")]
+    DeviceNotFound(String, String),
+
+    #[error("Could not load the {name} shader: {reason}.")]
+    Shader { name: &'static str, reason: String },
+
+    #[error("Could not load the scene {}: {reason}.", path.display())]
+    Scene { path: PathBuf, reason: String },
+
+    #[error("Could not render the still: {0}")]
+    Render(String),
+
+    #[error("Could not write {}: {reason}.", path.display())]
+    Write { path: PathBuf, reason: String },
+
+    #[error("{0} failed ({1}).")]
+    Vulkan(&'static str, vk::Result),
+}
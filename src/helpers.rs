@@ -24,9 +24,10 @@ pub unsafe extern "system" fn vulkan_debug_callback(
         CStr::from_ptr(callback_data.p_message).to_string_lossy()
     };
 
-    println!(
-        "{:?}:\n{:?} [{} ({})] : {}\n",
-        message_severity,
+    log::log!(
+        target: "vulkan",
+        log_level(message_severity),
+        "{:?} [{} ({})] : {}",
         message_type,
         message_id_name,
         &message_id_number.to_string(),
@@ -34,4 +35,27 @@ pub unsafe extern "system" fn vulkan_debug_callback(
     );
 
     vk::FALSE
+}
+
+fn log_level(message_severity: vk::DebugUtilsMessageSeverityFlagsEXT) -> log::Level {
+    match message_severity {
+        vk::DebugUtilsMessageSeverityFlagsEXT::ERROR => log::Level::Error,
+        vk::DebugUtilsMessageSeverityFlagsEXT::WARNING => log::Level::Warn,
+        vk::DebugUtilsMessageSeverityFlagsEXT::INFO => log::Level::Info,
+        _ => log::Level::Trace,
+    }
+}
+
+// The severities the logger lets through for the `vulkan` target,
+// so filtered messages aren't produced by the layer at all.
+pub fn vulkan_message_severities() -> vk::DebugUtilsMessageSeverityFlagsEXT {
+    [
+        vk::DebugUtilsMessageSeverityFlagsEXT::ERROR,
+        vk::DebugUtilsMessageSeverityFlagsEXT::WARNING,
+        vk::DebugUtilsMessageSeverityFlagsEXT::INFO,
+        vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE,
+    ]
+    .into_iter()
+    .filter(|&severity| log::log_enabled!(target: "vulkan", log_level(severity)))
+    .fold(vk::DebugUtilsMessageSeverityFlagsEXT::empty(), |flags, severity| flags | severity)
 }
\ No newline at end of file
@@ -7,25 +7,70 @@ mod entities;
 mod cameras;
 mod utils;
 mod config;
+mod ui;
+mod editor;
+mod capture;
+mod error;
+mod cli;
+mod commands;
 mod app;
 
 use utils::FPScalculator;
 use cameras::Camera;
 
+use cli::{Cli, Command};
+use error::AppError;
+
+use clap::Parser;
 use async_std::task;
 use std::sync::{Arc, Mutex};
+use std::path::Path;
 use std::time;
 
 use winit::{
-    event::{Event, WindowEvent, KeyboardInput, VirtualKeyCode},
+    event::{Event, WindowEvent, KeyboardInput},
     event_loop::{ControlFlow, EventLoop}
 };
 
 fn main() {
-    let event_loop = EventLoop::new();
+    // The GPUs found and the one used are logged at info.
+    // RUST_LOG=vulkan=info shows the validation layer's info messages as well
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn,hyper_oculus=info")).init();
+
+    let cli = Cli::parse();
+
+    // defaults, then config.toml, then the command line
+    let mut settings = config::Settings::load();
+    settings.override_with(&cli);
+
+    let result = match &cli.command {
+        Some(Command::Info { mesh }) => commands::info(mesh),
+        Some(Command::Export { scene, format, output }) => {
+            commands::export(scene, *format, output.as_deref(), &settings)
+        }
+        Some(Command::Render { output, .. }) => render(&cli, settings, output),
+        Some(Command::View { .. }) | None => view(&cli, settings),
+    };
+
+    if let Err(e) = result {
+        eprintln!("hyper-oculus: {}", e);
+        std::process::exit(1);
+    }
+}
 
-    let mut application = app::App::new(&event_loop);
+fn render(cli: &Cli, settings: config::Settings, output: &Path) -> Result<(), AppError> {
+    let (width, height) = (settings.window.width, settings.window.height);
 
+    let mut application = app::App::headless(cli, settings)?;
+
+    application.render_still(output, width, height)
+}
+
+fn view(cli: &Cli, settings: config::Settings) -> Result<(), AppError> {
+    let event_loop = EventLoop::new();
+    let started = settings.clone();
+
+    let mut application = app::App::new(&event_loop, cli, settings)?;
     let fps_calculator = Arc::new(Mutex::new(FPScalculator::new()));
     let fps_calculator_clone = Arc::clone(&fps_calculator);
     
@@ -42,19 +87,27 @@ fn main() {
     event_loop.run(move |event, _, control_flow| {
         *control_flow = ControlFlow::Poll;
 
-        application.handle_event(&event);
+        if let Err(e) = application.handle_event(&event) {
+            eprintln!("hyper-oculus: {}", e);
+            *control_flow = ControlFlow::ExitWithCode(1);
+            return;
+        }
 
         if let Event::RedrawEventsCleared = event {
             fps_calculator.lock().unwrap().count_one_frame();
         }
 
+        if let Event::LoopDestroyed = event {
+            application.settings().save(&started);
+        }
+
         if let Event::WindowEvent { event, .. } = event {
             *control_flow = match event {
                 WindowEvent::CloseRequested => ControlFlow::Exit,
                 WindowEvent::KeyboardInput { 
-                    input: KeyboardInput { virtual_keycode: Some(VirtualKeyCode::Escape), ..},
+                    input: KeyboardInput { virtual_keycode: Some(key), ..},
                     ..
-                } => ControlFlow::Exit,
+                } if key == application.settings().keys.quit => ControlFlow::Exit,
                 _ => ControlFlow::Poll,
             }
         }
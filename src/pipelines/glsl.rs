@@ -0,0 +1,36 @@
+// GLSL to SPIR-V with naga, shared by build.rs and the shader watcher.
+
+use naga::back::spv;
+use naga::front::glsl;
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use naga::{Module, ShaderStage};
+
+pub fn compile(source: &str, stage: ShaderStage) -> Result<Vec<u32>, String> {
+    write_spv(&parse(source, stage)?)
+}
+
+pub fn parse(source: &str, stage: ShaderStage) -> Result<Module, String> {
+    glsl::Parser::default().parse(&glsl::Options::from(stage), source).map_err(|errors| {
+        let lines: Vec<String> = errors
+            .iter()
+            .map(|error| {
+                let location = error.meta.location(source);
+                format!("line {}:{}: {}", location.line_number, location.line_position, error.kind)
+            })
+            .collect();
+
+        lines.join("\n")
+    })
+}
+
+pub fn write_spv(module: &Module) -> Result<Vec<u32>, String> {
+    let info = Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(module)
+        .map_err(|e| e.to_string())?;
+
+    // naga flips y by default, glslc does not
+    let mut options = spv::Options::default();
+    options.flags.remove(spv::WriterFlags::ADJUST_COORDINATE_SPACE);
+
+    spv::write_vec(module, &info, &options, None).map_err(|e| e.to_string())
+}
@@ -1,3 +1,8 @@
 
 mod pipeline;
-pub use pipeline::Pipeline;
\ No newline at end of file
+pub use pipeline::{ColorMask, Pipeline};
+
+mod glsl;
+
+mod shaders;
+pub use shaders::{ShaderCode, ShaderWatcher};
@@ -1,22 +1,43 @@
 
 
+use super::ShaderCode;
 use crate::entities::Vertex;
+use crate::error::AppError;
 
 use memoffset::*;
 
-use ash::util::*;
 use ash::vk;
 use std::ffi::CString;
-use std::io::Cursor;
 
 use std::mem;
 
+// The color channels a pipeline writes, e.g. one eye of an anaglyph.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorMask {
+    All,
+    Red,
+    Cyan,
+}
+
+impl ColorMask {
+    const ALL: [ColorMask; 3] = [ColorMask::All, ColorMask::Red, ColorMask::Cyan];
+
+    fn flags(&self) -> vk::ColorComponentFlags {
+        match self {
+            ColorMask::All => vk::ColorComponentFlags::RGBA,
+            ColorMask::Red => vk::ColorComponentFlags::R,
+            ColorMask::Cyan => vk::ColorComponentFlags::G | vk::ColorComponentFlags::B,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct Pipeline {
     device: Option<ash::Device>,
     render_pass: Option<vk::RenderPass>,
     pipeline_layout: Option<vk::PipelineLayout>,
-    pipeline: Option<vk::Pipeline>
+    // one pipeline for every `ColorMask`
+    pipelines: Option<Vec<vk::Pipeline>>
 }
 
 impl Pipeline {
@@ -25,13 +46,15 @@ impl Pipeline {
         surface_format: &vk::SurfaceFormatKHR,
         surface_resolution: &vk::Extent2D,
         descriptor_set_layout: &vk::DescriptorSetLayout,
-    ) -> Self {
+        polygon_mode: vk::PolygonMode,
+        shaders: &ShaderCode,
+    ) -> Result<Self, AppError> {
         let mut pipeline = Self::default();
         pipeline.device = Some(device);
 
         pipeline.create_render_pass(surface_format);
-        pipeline.create_graphics_pipeline(surface_resolution, descriptor_set_layout);
-        pipeline
+        pipeline.create_graphics_pipeline(surface_resolution, descriptor_set_layout, polygon_mode, shaders)?;
+        Ok(pipeline)
     }
 
     pub fn render_pass(&self) -> &vk::RenderPass {
@@ -42,8 +65,9 @@ impl Pipeline {
         self.pipeline_layout.as_ref().unwrap()
     }
 
-    pub fn pipeline(&self) -> &vk::Pipeline {
-        self.pipeline.as_ref().unwrap()
+    pub fn masked_pipeline(&self, mask: ColorMask) -> &vk::Pipeline {
+        let index = ColorMask::ALL.iter().position(|&m| m == mask).unwrap();
+        &self.pipelines.as_ref().unwrap()[index]
     }
 
     fn create_render_pass(&mut self, surface_format: &vk::SurfaceFormatKHR) {
@@ -110,31 +134,32 @@ impl Pipeline {
         self.render_pass = Some(render_pass);
     }
 
-    fn create_graphics_pipeline(&mut self, surface_resolution: &vk::Extent2D, descriptor_set_layout: &vk::DescriptorSetLayout) {
+    fn create_graphics_pipeline(
+        &mut self,
+        surface_resolution: &vk::Extent2D,
+        descriptor_set_layout: &vk::DescriptorSetLayout,
+        polygon_mode: vk::PolygonMode,
+        shaders: &ShaderCode,
+    ) -> Result<(), AppError> {
         let device = self.device.as_ref().unwrap();
         let render_pass = self.render_pass.as_ref().unwrap();
 
-        let mut vertex_spv_file = Cursor::new(&include_bytes!("../../data/shaders/vert.spv")[..]);
-        let mut frag_spv_file = Cursor::new(&include_bytes!("../../data/shaders/frag.spv")[..]);
-
-        let vertex_code = read_spv(&mut vertex_spv_file)
-            .expect("Failed to read vertex shader spv file");
-        let vertex_shader_info = vk::ShaderModuleCreateInfo::builder().code(&vertex_code);
-
-        let frag_code = read_spv(&mut frag_spv_file)
-            .expect("Failed to read fragment shader spv file");
-        let frag_shader_info = vk::ShaderModuleCreateInfo::builder().code(&frag_code);
+        let vertex_shader_info = vk::ShaderModuleCreateInfo::builder().code(&shaders.vertex);
+        let frag_shader_info = vk::ShaderModuleCreateInfo::builder().code(&shaders.fragment);
 
         let vertex_shader_module = unsafe {
             device
                 .create_shader_module(&vertex_shader_info, None)
-                .expect("Vertex shader module error")
+                .map_err(|e| AppError::Shader { name: "vertex", reason: e.to_string() })?
         };
 
         let fragment_shader_module = unsafe {
             device
                 .create_shader_module(&frag_shader_info, None)
-                .expect("Fragment shader module error")
+                .map_err(|e| {
+                    device.destroy_shader_module(vertex_shader_module, None);
+                    AppError::Shader { name: "fragment", reason: e.to_string() }
+                })?
         };
 
         let descriptor_set_layouts = &[*descriptor_set_layout];
@@ -218,7 +243,7 @@ impl Pipeline {
         let rasterization_info = vk::PipelineRasterizationStateCreateInfo {
             front_face: vk::FrontFace::COUNTER_CLOCKWISE,
             line_width: 1.0,
-            polygon_mode: vk::PolygonMode::LINE,
+            polygon_mode,
             cull_mode: vk::CullModeFlags::NONE,
             ..Default::default()
         };
@@ -242,7 +267,7 @@ impl Pipeline {
             max_depth_bounds: 1.0,
             ..Default::default()
         };
-        let color_blend_attachment_states = [vk::PipelineColorBlendAttachmentState {
+        let color_blend_attachment_states = ColorMask::ALL.map(|mask| [vk::PipelineColorBlendAttachmentState {
             blend_enable: 0,
             src_color_blend_factor: vk::BlendFactor::SRC_COLOR,
             dst_color_blend_factor: vk::BlendFactor::ONE_MINUS_DST_COLOR,
@@ -250,62 +275,75 @@ impl Pipeline {
             src_alpha_blend_factor: vk::BlendFactor::ZERO,
             dst_alpha_blend_factor: vk::BlendFactor::ZERO,
             alpha_blend_op: vk::BlendOp::ADD,
-            color_write_mask: vk::ColorComponentFlags::R
-                | vk::ColorComponentFlags::G
-                | vk::ColorComponentFlags::B
-                | vk::ColorComponentFlags::A,
-        }];
-        let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
-            .logic_op(vk::LogicOp::CLEAR)
-            .attachments(&color_blend_attachment_states);
+            color_write_mask: mask.flags(),
+        }]);
+        let color_blend_states: Vec<vk::PipelineColorBlendStateCreateInfo> = color_blend_attachment_states
+            .iter()
+            .map(|attachments| vk::PipelineColorBlendStateCreateInfo::builder()
+                .logic_op(vk::LogicOp::CLEAR)
+                .attachments(attachments)
+                .build())
+            .collect();
 
         let dynamic_state = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
         let dynamic_state_info =
             vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(&dynamic_state);
 
-        let graphics_pipeline_info = vk::GraphicsPipelineCreateInfo::builder()
-            .stages(&shader_stage_create_infos)
-            .vertex_input_state(&vertex_input_state_info)
-            .input_assembly_state(&vertex_input_assembly_state_info)
-            .viewport_state(&viewport_state_info)
-            .rasterization_state(&rasterization_info)
-            .multisample_state(&multisample_state_info)
-            .depth_stencil_state(&depth_state_info)
-            .color_blend_state(&color_blend_state)
-            .dynamic_state(&dynamic_state_info)
-            .layout(pipeline_layout)
-            .render_pass(*render_pass);
+        let graphics_pipeline_infos: Vec<vk::GraphicsPipelineCreateInfo> = color_blend_states
+            .iter()
+            .map(|color_blend_state| vk::GraphicsPipelineCreateInfo::builder()
+                .stages(&shader_stage_create_infos)
+                .vertex_input_state(&vertex_input_state_info)
+                .input_assembly_state(&vertex_input_assembly_state_info)
+                .viewport_state(&viewport_state_info)
+                .rasterization_state(&rasterization_info)
+                .multisample_state(&multisample_state_info)
+                .depth_stencil_state(&depth_state_info)
+                .color_blend_state(color_blend_state)
+                .dynamic_state(&dynamic_state_info)
+                .layout(pipeline_layout)
+                .render_pass(*render_pass)
+                .build())
+            .collect();
 
         let graphics_pipelines = unsafe {
-            device
-                .create_graphics_pipelines(
-                    vk::PipelineCache::null(),
-                    &[graphics_pipeline_info.build()],
-                    None,
-                )
-                .expect("Unable to create graphics pipeline")
+            device.create_graphics_pipelines(
+                vk::PipelineCache::null(),
+                &graphics_pipeline_infos,
+                None,
+            )
         };
 
-        let graphics_pipeline = graphics_pipelines[0];
-
         self.pipeline_layout = Some(pipeline_layout);
-        self.pipeline = Some(graphics_pipeline);
 
         unsafe {
             device.destroy_shader_module(vertex_shader_module, None);
             device.destroy_shader_module(fragment_shader_module, None);
         }
+
+        let graphics_pipelines = graphics_pipelines
+            .map_err(|(_, e)| AppError::Vulkan("Creating the graphics pipelines", e))?;
+        self.pipelines = Some(graphics_pipelines);
+
+        Ok(())
     }
 
 }
 
+// `new` may have failed part way.
 impl Drop for Pipeline {
     fn drop(&mut self) {
         let device = self.device.as_ref().unwrap();
         unsafe {
-            device.destroy_pipeline(self.pipeline.unwrap(), None);
-            device.destroy_pipeline_layout(self.pipeline_layout.unwrap(), None);
-            device.destroy_render_pass(self.render_pass.unwrap(), None);
+            for pipeline in self.pipelines.take().unwrap_or_default() {
+                device.destroy_pipeline(pipeline, None);
+            }
+            if let Some(pipeline_layout) = self.pipeline_layout.take() {
+                device.destroy_pipeline_layout(pipeline_layout, None);
+            }
+            if let Some(render_pass) = self.render_pass.take() {
+                device.destroy_render_pass(render_pass, None);
+            }
         }
     }
 }
\ No newline at end of file
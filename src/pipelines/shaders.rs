@@ -0,0 +1,122 @@
+use super::glsl;
+use crate::error::AppError;
+
+use ash::util::read_spv;
+use naga::ShaderStage;
+
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+const SOURCES: [(&str, &str, ShaderStage); 2] = [
+    ("vertex", "src/shaders/shader.vert", ShaderStage::Vertex),
+    ("fragment", "src/shaders/shader.frag", ShaderStage::Fragment),
+];
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// SPIR-V of the vertex and the fragment shader.
+#[derive(Clone)]
+pub struct ShaderCode {
+    pub vertex: Vec<u32>,
+    pub fragment: Vec<u32>,
+}
+
+impl ShaderCode {
+    // The shaders built into the binary, compiled by build.rs.
+    pub fn embedded() -> Result<Self, AppError> {
+        let vertex = read_spv(&mut Cursor::new(&include_bytes!(concat!(env!("OUT_DIR"), "/vert.spv"))[..]))
+            .map_err(|e| AppError::Shader { name: "vertex", reason: e.to_string() })?;
+        let fragment = read_spv(&mut Cursor::new(&include_bytes!(concat!(env!("OUT_DIR"), "/frag.spv"))[..]))
+            .map_err(|e| AppError::Shader { name: "fragment", reason: e.to_string() })?;
+
+        Ok(Self { vertex, fragment })
+    }
+}
+
+// Development mode: recompile the shaders when a file in src/shaders changes.
+pub struct ShaderWatcher {
+    root: PathBuf,
+    last_poll: Option<Instant>,
+    modified: Option<SystemTime>,
+    message: Option<Result<String, String>>,
+}
+
+impl ShaderWatcher {
+    pub fn new() -> Self {
+        Self {
+            root: PathBuf::from(env!("CARGO_MANIFEST_DIR")),
+            last_poll: None,
+            modified: None,
+            message: None,
+        }
+    }
+
+    // New shader code when the files changed since the last build, the first time as well.
+    pub fn poll(&mut self) -> Option<ShaderCode> {
+        if self.last_poll.is_some_and(|last_poll| last_poll.elapsed() < POLL_INTERVAL) {
+            return None;
+        }
+        self.last_poll = Some(Instant::now());
+
+        let modified = self.newest_modification();
+        if modified.is_some() && modified == self.modified {
+            return None;
+        }
+
+        self.modified = modified;
+
+        match self.build() {
+            Ok(code) => {
+                self.message = Some(Ok("Compiled.".to_string()));
+                Some(code)
+            }
+            Err(error) => {
+                self.fail(error);
+                None
+            }
+        }
+    }
+
+    // Report shaders that compiled but couldn't be used.
+    pub fn fail(&mut self, error: String) {
+        log::warn!("Keeping the previous shaders: {}", error);
+        self.message = Some(Err(error));
+    }
+
+    pub fn ui(&self, ui: &mut egui::Ui) {
+        ui.label(format!("Watching {}", self.root.join("src/shaders").display()));
+
+        match self.message.as_ref() {
+            Some(Ok(message)) => { ui.label(message); }
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::RED, "Kept the previous shaders:");
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            None => (),
+        }
+    }
+
+    fn newest_modification(&self) -> Option<SystemTime> {
+        SOURCES
+            .iter()
+            .filter_map(|(_, path, _)| std::fs::metadata(self.root.join(path)).and_then(|m| m.modified()).ok())
+            .max()
+    }
+
+    fn build(&self) -> Result<ShaderCode, String> {
+        let mut code = Vec::with_capacity(SOURCES.len());
+
+        for (name, path, stage) in SOURCES {
+            let path = self.root.join(path);
+            let source = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+            code.push(glsl::compile(&source, stage).map_err(|e| format!("{} shader:\n{}", name, e))?);
+        }
+
+        let fragment = code.pop().unwrap();
+        let vertex = code.pop().unwrap();
+
+        Ok(ShaderCode { vertex, fragment })
+    }
+}
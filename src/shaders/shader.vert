@@ -7,6 +7,9 @@ layout(binding = 0) uniform UniformBufferObject {
     vec4 cam4_row;
     mat4 cam3_trans;
     float cam4_const;
+    // 0 perspective, 1 orthographic, 2 stereographic
+    int projection;
+    float sphere_radius;
 } ubo;
 
 layout(location = 0) in vec4 inPosition;
@@ -17,7 +20,16 @@ layout(location = 0) out vec3 fragColor;
 vec4 transform(in vec4 position) {
     vec4 pos4d = ((ubo.cam4_trans * inPosition + ubo.cam4_col) / (ubo.cam4_row * inPosition + ubo.cam4_const));
 
-    pos4d = pos4d / pos4d.w;
+    if (ubo.projection == 0) {
+        pos4d = pos4d / pos4d.w;
+    } else if (ubo.projection == 1) {
+        pos4d = vec4(pos4d.xyz, 1.0);
+    } else {
+        // from the pole at w = sphere_radius onto the hyperplane w = 0
+        float r = ubo.sphere_radius;
+        pos4d = vec4(pos4d.xyz * r / (r - pos4d.w), 1.0);
+    }
+
     return ubo.cam3_trans * pos4d;
 }
 
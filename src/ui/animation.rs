@@ -0,0 +1,64 @@
+use crate::core::transform::{Transform4, PLANES};
+use crate::entities::Animation;
+
+const AXES: [&str; 4] = ["x", "y", "z", "w"];
+
+// Edit the animation of an entity; new keyframes take the current `pose`.
+pub fn animation_ui(ui: &mut egui::Ui, animation: &mut Option<Animation>, pose: &Transform4) {
+    let mut animated = animation.is_some();
+    ui.checkbox(&mut animated, "animated");
+
+    if !animated {
+        *animation = None;
+        return;
+    }
+
+    let animation = animation.get_or_insert_with(Animation::default);
+
+    ui.horizontal(|ui| {
+        if ui.button("Double rotation").clicked() {
+            *animation = Animation::double_rotation(std::f32::consts::FRAC_PI_4);
+        }
+        if ui.button("Stop").clicked() {
+            *animation = Animation::default();
+        }
+    });
+
+    ui.label("spin (degrees per second)");
+    egui::Grid::new("animation_spin").show(ui, |ui| {
+        for (plane, spin) in animation.spin.iter_mut().enumerate() {
+            let mut degrees = spin.to_degrees();
+            ui.label(PLANES[plane]);
+            if ui.add(egui::DragValue::new(&mut degrees).speed(1.0)).changed() {
+                *spin = degrees.to_radians();
+            }
+            if plane % 3 == 2 {
+                ui.end_row();
+            }
+        }
+    });
+
+    ui.label("velocity");
+    ui.horizontal(|ui| {
+        for (axis, name) in AXES.iter().enumerate() {
+            ui.label(*name);
+            ui.add(egui::DragValue::new(&mut animation.velocity[axis]).speed(0.01));
+        }
+    });
+
+    ui.label(format!(
+        "{} keyframes, looping every {:.2} s",
+        animation.keyframes.len(),
+        animation.duration(),
+    ));
+    ui.horizontal(|ui| {
+        if ui.button("Add keyframe").clicked() {
+            // one second after the last keyframe
+            let time = animation.keyframes.last().map_or(0.0, |last| last.time + 1.0);
+            animation.add_keyframe(time, *pose);
+        }
+        if ui.button("Clear").clicked() {
+            animation.keyframes.clear();
+        }
+    });
+}
@@ -0,0 +1,32 @@
+use crate::utils::Clock;
+
+pub fn clock_ui(ui: &mut egui::Ui, clock: &mut Clock) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut clock.paused, "paused");
+        if ui.add_enabled(clock.paused, egui::Button::new("Step")).clicked() {
+            clock.step();
+        }
+        if ui.button("Reset").clicked() {
+            clock.reset();
+        }
+    });
+
+    ui.add(egui::Slider::new(&mut clock.scale, 0.0..=4.0).text("time scale"));
+
+    let mut fixed = clock.fixed_step.is_some();
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut fixed, "fixed step");
+        if let Some(step) = clock.fixed_step.as_mut() {
+            let mut fps = 1.0 / *step;
+            ui.add(egui::DragValue::new(&mut fps).clamp_range(1.0..=240.0).suffix(" fps"));
+            *step = 1.0 / fps;
+        }
+    });
+    match (fixed, clock.fixed_step) {
+        (true, None) => clock.fixed_step = Some(1.0 / 60.0),
+        (false, Some(_)) => clock.fixed_step = None,
+        _ => (),
+    }
+
+    ui.label(format!("time: {:.3} s, frame {}", clock.time(), clock.frame()));
+}
@@ -0,0 +1,45 @@
+use crate::entities::{Check, Entity};
+
+// A window with the analysis of the shown entity.
+#[derive(Default)]
+pub struct Inspector {
+    pub open: bool,
+    checks: Vec<Check>,
+}
+
+impl Inspector {
+    // Analyze `entity` again, whenever it was replaced.
+    pub fn analyze(&mut self, entity: &Entity) {
+        self.checks = entity.analyze().checks();
+    }
+
+    pub fn failed(&self) -> Vec<&Check> {
+        self.checks.iter().filter(|check| check.ok == Some(false)).collect()
+    }
+
+    pub fn summary_ui(&mut self, ui: &mut egui::Ui) {
+        match self.failed().len() {
+            0 => ui.label("All checks passed."),
+            failed => ui.colored_label(egui::Color32::RED, format!("{} checks failed.", failed)),
+        };
+        ui.checkbox(&mut self.open, "show the inspector");
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let checks = &self.checks;
+
+        egui::Window::new("Inspector").open(&mut self.open).show(ctx, |ui| {
+            egui::Grid::new("inspector_checks").striped(true).show(ui, |ui| {
+                for check in checks.iter() {
+                    ui.label(check.name);
+                    match check.ok {
+                        Some(true) => ui.colored_label(egui::Color32::GREEN, &check.value),
+                        Some(false) => ui.colored_label(egui::Color32::RED, &check.value),
+                        None => ui.label(&check.value),
+                    };
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,168 @@
+use crate::cameras::Camera4;
+use crate::entities::Entity;
+
+extern crate nalgebra as na;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LabelContent {
+    Index,
+    Name,
+    Coordinates,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LabelFilter {
+    All,
+    Vertices,
+    Cell,
+}
+
+// Labels drawn next to the projected vertices and edges of an entity.
+pub struct Labels {
+    pub show_vertices: bool,
+    pub show_edges: bool,
+    pub content: LabelContent,
+    pub filter: LabelFilter,
+    // e.g. "0, 3, 5-7"
+    pub vertex_list: String,
+    pub cell: usize,
+}
+
+impl Default for Labels {
+    fn default() -> Self {
+        Self {
+            show_vertices: true,
+            show_edges: false,
+            content: LabelContent::Index,
+            filter: LabelFilter::All,
+            vertex_list: String::new(),
+            cell: 0,
+        }
+    }
+}
+
+impl Labels {
+    pub fn settings_ui(&mut self, ui: &mut egui::Ui, entity: &Entity) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_vertices, "vertices");
+            ui.checkbox(&mut self.show_edges, "edges");
+        });
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.content, LabelContent::Index, "index");
+            ui.radio_value(&mut self.content, LabelContent::Name, "name");
+            ui.radio_value(&mut self.content, LabelContent::Coordinates, "coordinates");
+        });
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.filter, LabelFilter::All, "all");
+            ui.radio_value(&mut self.filter, LabelFilter::Vertices, "vertices");
+            ui.radio_value(&mut self.filter, LabelFilter::Cell, "cell");
+        });
+
+        match self.filter {
+            LabelFilter::All => (),
+            LabelFilter::Vertices => {
+                ui.text_edit_singleline(&mut self.vertex_list);
+            }
+            LabelFilter::Cell => {
+                let cell_count = entity.topology().cells.len();
+                if cell_count == 0 {
+                    ui.label("no cells");
+                } else {
+                    ui.add(egui::Slider::new(&mut self.cell, 0..=cell_count - 1).text("cell"));
+                }
+            }
+        }
+    }
+
+    // Draw the labels inside `screen`, the rectangle the camera renders to.
+    pub fn paint(&self, ctx: &egui::Context, camera: &Camera4, entity: &Entity, screen: egui::Rect) {
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("labels"),
+        )).with_clip_rect(screen);
+        let font = egui::FontId::monospace(12.0);
+        let transform = entity.transform();
+        let vertices = entity.vertices();
+        let topology = entity.topology();
+
+        let (shown_vertices, shown_edges) = match self.filter {
+            LabelFilter::All => (
+                (0..vertices.len()).collect(),
+                (0..topology.edges.len()).collect(),
+            ),
+            LabelFilter::Vertices => {
+                let list = parse_indices(&self.vertex_list, vertices.len());
+                let edges = (0..topology.edges.len())
+                    .filter(|&e| topology.edges[e].iter().all(|&v| list.contains(&(v as usize))))
+                    .collect();
+                (list, edges)
+            }
+            LabelFilter::Cell if self.cell < topology.cells.len() => (
+                topology.cell_vertices(self.cell).iter().map(|&v| v as usize).collect(),
+                topology.cell_edges(self.cell),
+            ),
+            LabelFilter::Cell => (Vec::new(), Vec::new()),
+        };
+
+        if self.show_vertices {
+            for &i in shown_vertices.iter().filter(|&&i| i < vertices.len()) {
+                let pos = vertices[i].pos;
+                let text = match self.content {
+                    LabelContent::Index => i.to_string(),
+                    LabelContent::Name => entity.vertex_name(i).unwrap_or_else(|| i.to_string()),
+                    LabelContent::Coordinates => format!(
+                        "({:.2}, {:.2}, {:.2}, {:.2})",
+                        pos[0], pos[1], pos[2], pos[3],
+                    ),
+                };
+
+                if let Some(p) = super::to_screen(camera.project(&transform, &pos), screen) {
+                    painter.text(p, egui::Align2::LEFT_BOTTOM, text, font.clone(), egui::Color32::WHITE);
+                }
+            }
+        }
+
+        if self.show_edges {
+            for &e in shown_edges.iter() {
+                let [a, b] = topology.edges[e].map(|v| na::Vector4::from(vertices[v as usize].pos));
+                let middle = (a + b) / 2.0;
+                let text = match self.content {
+                    LabelContent::Coordinates => format!("{:.3}", (b - a).norm()),
+                    _ => format!("e{}", e),
+                };
+
+                let projected = camera.project(&transform, &[middle[0], middle[1], middle[2], middle[3]]);
+                if let Some(p) = super::to_screen(projected, screen) {
+                    painter.text(p, egui::Align2::CENTER_CENTER, text, font.clone(), egui::Color32::YELLOW);
+                }
+            }
+        }
+    }
+}
+
+// Parse a list like "0, 3, 5-7", keeping the indices below `count`.
+fn parse_indices(text: &str, count: usize) -> Vec<usize> {
+    let mut indices = Vec::new();
+
+    for part in text.split(',').map(str::trim) {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                if let (Ok(first), Ok(last)) = (first.trim().parse::<usize>(), last.trim().parse::<usize>()) {
+                    // e.g. 0-4000000000 only goes as far as the vertices
+                    indices.extend(first..last.saturating_add(1).min(count));
+                }
+            }
+            None => {
+                if let Ok(index) = part.parse() {
+                    if index < count {
+                        indices.push(index);
+                    }
+                }
+            }
+        }
+    }
+
+    indices
+}
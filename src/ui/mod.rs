@@ -0,0 +1,33 @@
+mod labels;
+pub use labels::Labels;
+
+mod animation;
+pub use animation::animation_ui;
+
+mod clock;
+pub use clock::clock_ui;
+
+mod inspector;
+pub use inspector::Inspector;
+
+mod scenes;
+pub use scenes::ScenePicker;
+
+extern crate nalgebra as na;
+
+// Map normalized device coordinates to a point on the screen, if it is visible.
+pub fn to_screen(ndc: Option<na::Point3<f32>>, screen: egui::Rect) -> Option<egui::Pos2> {
+    let ndc = ndc?;
+    // e.g. a vertex on the plane of the 4D camera
+    if ndc.iter().any(|x| !x.is_finite()) {
+        return None;
+    }
+    if ndc[0].abs() > 1.0 || ndc[1].abs() > 1.0 || ndc[2] < 0.0 || ndc[2] > 1.0 {
+        return None;
+    }
+
+    Some(egui::pos2(
+        screen.left() + (ndc[0] + 1.0) / 2.0 * screen.width(),
+        screen.top() + (ndc[1] + 1.0) / 2.0 * screen.height(),
+    ))
+}
@@ -0,0 +1,71 @@
+use crate::entities::{Entity, Preset};
+
+// Picks a built-in scene to replace the shown entity.
+pub struct ScenePicker {
+    pub preset: Preset,
+    pub resolution: usize,
+    pub bounds: f32,
+    requested: bool,
+    error: Option<String>,
+}
+
+impl Default for ScenePicker {
+    fn default() -> Self {
+        Self {
+            preset: Preset::Tesseract,
+            resolution: 8,
+            bounds: 1.5,
+            requested: false,
+            error: None,
+        }
+    }
+}
+
+impl ScenePicker {
+    // The entity of the picked scene, once after the button was pressed.
+    // An empty scene can't be drawn, the shown one stays then.
+    pub fn take_request(&mut self) -> Option<Entity> {
+        if !std::mem::take(&mut self.requested) {
+            return None;
+        }
+
+        let entity = self.preset.entity(self.resolution, self.bounds);
+        if entity.is_empty() {
+            self.error = Some("the zero set is empty in these bounds".to_string());
+            return None;
+        }
+
+        self.error = None;
+        Some(entity)
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let previous = self.preset;
+        egui::ComboBox::from_label("preset")
+            .selected_text(self.preset.name())
+            .show_ui(ui, |ui| {
+                for preset in Preset::ALL {
+                    ui.selectable_value(&mut self.preset, preset, preset.name());
+                }
+            });
+
+        if let Some(resolutions) = self.preset.resolutions() {
+            // the resolutions of surfaces and of volumes are far apart
+            if self.preset != previous {
+                self.resolution = (resolutions.start() + resolutions.end()) / 2;
+            }
+            ui.add(egui::Slider::new(&mut self.resolution, resolutions).text("resolution"));
+        }
+        if self.preset.has_bounds() {
+            ui.add(egui::Slider::new(&mut self.bounds, 0.5..=3.0).text("bounds"));
+        }
+
+        if ui.button("Load").clicked() {
+            self.requested = true;
+        }
+
+        if let Some(error) = self.error.as_ref() {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+}
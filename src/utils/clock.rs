@@ -0,0 +1,153 @@
+use std::time::Instant;
+
+// The simulation clock. Every frame advances it once with `tick`, and cameras
+// and animations only ever see its `dt` and `time`.
+pub struct Clock {
+    last: Option<Instant>,
+    time: f64,
+    dt: f32,
+    frame: u64,
+
+    pub paused: bool,
+    pub scale: f32,
+    // seconds per frame, ignoring the wall clock, for frame-exact replays
+    pub fixed_step: Option<f32>,
+    step_requested: bool,
+}
+
+// The step of a single frame advance while paused, without a fixed step.
+const DEFAULT_STEP: f32 = 1.0 / 60.0;
+
+impl Clock {
+    pub fn new() -> Self {
+        Self {
+            last: None,
+            time: 0.0,
+            dt: 0.0,
+            frame: 0,
+            paused: false,
+            scale: 1.0,
+            fixed_step: None,
+            step_requested: false,
+        }
+    }
+
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        let real = self.last.map_or(0.0, |last| (now - last).as_secs_f32());
+        self.last = Some(now);
+
+        let step = std::mem::take(&mut self.step_requested);
+        self.dt = if self.paused && !step {
+            0.0
+        } else if step {
+            self.fixed_step.unwrap_or(DEFAULT_STEP) * self.scale
+        } else {
+            self.fixed_step.unwrap_or(real) * self.scale
+        };
+
+        if self.dt > 0.0 {
+            self.time += self.dt as f64;
+            self.frame += 1;
+        }
+    }
+
+    // Advance a single frame on the next tick while paused.
+    pub fn step(&mut self) {
+        self.step_requested = true;
+    }
+
+    pub fn reset(&mut self) {
+        self.time = 0.0;
+        self.frame = 0;
+    }
+
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time as f32
+    }
+
+    // The number of frames the simulation has advanced.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn paused_time_stands_still() {
+        let mut clock = Clock::new();
+        clock.paused = true;
+
+        for _ in 0..3 {
+            sleep(Duration::from_millis(2));
+            clock.tick();
+        }
+
+        assert_eq!((clock.dt(), clock.time(), clock.frame()), (0.0, 0.0, 0));
+    }
+
+    #[test]
+    fn step_advances_one_fixed_step() {
+        let mut clock = Clock::new();
+        clock.paused = true;
+        clock.fixed_step = Some(0.25);
+
+        clock.step();
+        clock.tick();
+        assert_eq!((clock.dt(), clock.time(), clock.frame()), (0.25, 0.25, 1));
+
+        clock.tick();
+        assert_eq!((clock.dt(), clock.time(), clock.frame()), (0.0, 0.25, 1));
+
+        clock.fixed_step = None;
+        clock.step();
+        clock.tick();
+        assert_eq!(clock.dt(), DEFAULT_STEP);
+    }
+
+    #[test]
+    fn scale_multiplies_dt() {
+        let mut clock = Clock::new();
+        clock.fixed_step = Some(0.25);
+        clock.scale = 3.0;
+
+        clock.tick();
+        clock.tick();
+
+        assert_eq!((clock.dt(), clock.time(), clock.frame()), (0.75, 1.5, 2));
+    }
+
+    #[test]
+    fn fixed_steps_replay_exactly() {
+        // the wall clock differs between the runs
+        let run = |pause: Duration| {
+            let mut clock = Clock::new();
+            clock.fixed_step = Some(1.0 / 60.0);
+            clock.scale = 1.5;
+
+            (0..100)
+                .map(|i| {
+                    sleep(pause);
+                    clock.paused = (40..50).contains(&i);
+                    if i == 45 {
+                        clock.step();
+                    }
+                    clock.tick();
+                    (clock.dt(), clock.time(), clock.frame())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(Duration::ZERO), run(Duration::from_micros(300)));
+    }
+}
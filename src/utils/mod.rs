@@ -1,3 +1,5 @@
 mod fps_calculator;
+mod clock;
 
-pub use fps_calculator::FPScalculator;
\ No newline at end of file
+pub use fps_calculator::FPScalculator;
+pub use clock::Clock;